@@ -0,0 +1,77 @@
+//! Times flow path updates against a 60 fps frame, run with `cargo bench --bench flow_field`
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use the_last_hope::map::{Building, BuildingType, Map};
+
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+const RUNS: u32 = 200;
+
+fn building(
+    building_type: BuildingType,
+    bottom_left: (usize, usize),
+    width: usize,
+    height: usize,
+) -> Building {
+    Building {
+        building_type,
+        bottom_left,
+        width,
+        height,
+        max_health: 100.,
+        health: 100.,
+    }
+}
+
+fn report(name: &str, total: Duration) {
+    let average = total / RUNS;
+    let verdict = if average <= FRAME_BUDGET {
+        "fits"
+    } else {
+        "OVER"
+    };
+    println!(
+        "{:<24} {:>10.3?} per run, {} the {:?} frame budget",
+        name, average, verdict, FRAME_BUDGET
+    );
+}
+
+fn main() {
+    let mut building_hash_map = HashMap::new();
+    building_hash_map.insert(0, building(BuildingType::Sentry, (116, 131), 18, 12));
+    let mut map = Map::new(&building_hash_map);
+    let wall = building(BuildingType::Baricade, (60, 140), 40, 4);
+    let bigger_wall = building(BuildingType::Baricade, (60, 140), 40, 8);
+
+    let mut full_rebuild = Duration::ZERO;
+    let mut add = Duration::ZERO;
+    let mut resize = Duration::ZERO;
+    let mut remove = Duration::ZERO;
+    let mut kill_zone = Duration::ZERO;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        map.build_flow_path();
+        full_rebuild += started.elapsed();
+
+        let started = Instant::now();
+        map.add_building(1, &wall);
+        add += started.elapsed();
+
+        let started = Instant::now();
+        map.resize_building(1, &wall, &bigger_wall);
+        resize += started.elapsed();
+
+        let started = Instant::now();
+        map.remove_building(&bigger_wall);
+        remove += started.elapsed();
+
+        let started = Instant::now();
+        map.add_kill_zone((90, 125), 12, 50);
+        map.remove_kill_zone((90, 125), 12, 50);
+        kill_zone += started.elapsed();
+    }
+    report("full rebuild", full_rebuild);
+    report("add building", add);
+    report("resize building", resize);
+    report("remove building", remove);
+    report("add and remove kill zone", kill_zone);
+}
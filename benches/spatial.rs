@@ -0,0 +1,124 @@
+//! Times enemy queries on big waves against a 60 fps frame, run with `cargo bench --bench spatial`
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+use the_last_hope::enemy::Enemy;
+use the_last_hope::spatial::SpatialIndex;
+use the_last_hope::Simulation;
+
+const FRAME_BUDGET: Duration = Duration::from_micros(16_667);
+const RUNS: u32 = 200;
+const WAVE_SIZES: [usize; 3] = [1_000, 5_000, 10_000];
+/// queries of each kind made every run, more than a busy frame of sentries and explosions
+const QUERIES: usize = 100;
+
+fn report(name: &str, total: Duration, runs: u32) {
+    let average = total / runs;
+    let verdict = if average <= FRAME_BUDGET {
+        "fits"
+    } else {
+        "OVER"
+    };
+    println!(
+        "{:<32} {:>10.3?} per run, {} the {:?} frame budget",
+        name, average, verdict, FRAME_BUDGET
+    );
+}
+
+/// A simulation with a wave of enemies scattered over the map
+fn simulation_with_wave(enemy_count: usize, rng: &mut StdRng) -> Simulation {
+    let mut simulation = Simulation::with_seed(1);
+    let archetype = simulation.archetypes.get("grunt").unwrap().clone();
+    simulation.enemy_alive_list = (0..enemy_count)
+        .map(|_| {
+            let position = (rng.gen_range(-480.0..480.), rng.gen_range(-480.0..480.));
+            Enemy::from_archetype(&archetype, position, 100.)
+        })
+        .collect();
+    simulation
+}
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let query_points: Vec<(f32, f32)> = (0..QUERIES)
+        .map(|_| (rng.gen_range(-500.0..500.), rng.gen_range(-500.0..500.)))
+        .collect();
+    for enemy_count in WAVE_SIZES {
+        let mut simulation = simulation_with_wave(enemy_count, &mut rng);
+        let enemies = &simulation.enemy_alive_list;
+        let mut spatial_index = SpatialIndex::default();
+
+        let mut rebuild = Duration::ZERO;
+        let mut in_radius = Duration::ZERO;
+        let mut in_rect = Duration::ZERO;
+        let mut nearest = Duration::ZERO;
+        let mut list_walk = Duration::ZERO;
+        let mut found = 0;
+        for _ in 0..RUNS {
+            let started = Instant::now();
+            spatial_index.rebuild(enemies);
+            rebuild += started.elapsed();
+
+            let started = Instant::now();
+            for point in &query_points {
+                found += spatial_index.in_radius(*point, 70.).len();
+            }
+            in_radius += started.elapsed();
+
+            let started = Instant::now();
+            for point in &query_points {
+                found += spatial_index
+                    .in_rect(*point, (point.0 + 60., point.1 + 60.))
+                    .len();
+            }
+            in_rect += started.elapsed();
+
+            let started = Instant::now();
+            for point in &query_points {
+                found += spatial_index.nearest(*point, 5).len();
+            }
+            nearest += started.elapsed();
+
+            // the same radius queries walking the whole list, for comparison
+            let started = Instant::now();
+            for point in &query_points {
+                found += enemies
+                    .iter()
+                    .filter(|enemy| {
+                        let dif_x = enemy.position.0 - point.0;
+                        let dif_y = enemy.position.1 - point.1;
+                        dif_x * dif_x + dif_y * dif_y <= 70. * 70.
+                    })
+                    .count();
+            }
+            list_walk += started.elapsed();
+        }
+        println!("{} enemies, {} found", enemy_count, found);
+        report("rebuild", rebuild, RUNS);
+        report(&format!("{} radius queries", QUERIES), in_radius, RUNS);
+        report(&format!("{} rectangle queries", QUERIES), in_rect, RUNS);
+        report(&format!("{} nearest 5 queries", QUERIES), nearest, RUNS);
+        report(&format!("{} radius list walks", QUERIES), list_walk, RUNS);
+
+        // a whole simulation step, enemies walking the flow path included
+        let steps = 20;
+        let started = Instant::now();
+        for _ in 0..steps {
+            simulation.step(1. / 60.);
+        }
+        report("simulation step", started.elapsed(), steps);
+
+        // the same wave lying dead with auto collect queueing every body, nobody to carry them
+        let mut simulation = simulation_with_wave(enemy_count, &mut rng);
+        simulation.enemy_dead_list = std::mem::take(&mut simulation.enemy_alive_list);
+        simulation.worker_list.clear();
+        simulation.auto_collect = true;
+        simulation.auto_collect_radius = 1_000.;
+        simulation.step(1. / 60.);
+        let started = Instant::now();
+        for _ in 0..steps {
+            simulation.step(1. / 60.);
+        }
+        report("auto collect step", started.elapsed(), steps);
+    }
+}
@@ -0,0 +1,98 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Where the game looks for designer edited archetypes
+pub const ARCHETYPE_PATH: &str = "assets/enemies.toml";
+/// Archetypes baked into the binary, used when no data file is found
+const DEFAULT_ARCHETYPES: &str = include_str!("../assets/enemies.toml");
+
+/// One kind of enemy as described in the archetype data file
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct EnemyArchetype {
+    pub name: String,
+    pub speed: u32,
+    /// health rolled between [min, max] on spawn before the wave multiplier
+    pub health: [f32; 2],
+    pub size: f32,
+    /// triangle base as a fraction of its size
+    #[serde(default = "default_width")]
+    pub width: f32,
+    pub color: [u8; 3],
+    pub corpse_value: u32,
+    /// damage per second dealt to the building the enemy reached
+    #[serde(default = "default_attack_damage")]
+    pub attack_damage: f32,
+    #[serde(default = "default_spawn_weight")]
+    pub spawn_weight: f32,
+    #[serde(default = "default_first_wave")]
+    pub first_wave: u32,
+}
+fn default_width() -> f32 {
+    0.5
+}
+fn default_attack_damage() -> f32 {
+    10.
+}
+fn default_spawn_weight() -> f32 {
+    1.
+}
+fn default_first_wave() -> u32 {
+    1
+}
+/// Every enemy type the simulation can spawn
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ArchetypeRegistry {
+    #[serde(rename = "archetype")]
+    pub archetypes: Vec<EnemyArchetype>,
+}
+impl Default for ArchetypeRegistry {
+    fn default() -> Self {
+        ArchetypeRegistry::from_toml(DEFAULT_ARCHETYPES).expect("bundled enemies.toml is invalid")
+    }
+}
+impl ArchetypeRegistry {
+    pub fn from_toml(text: &str) -> io::Result<ArchetypeRegistry> {
+        let registry: ArchetypeRegistry = toml::from_str(text)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        if registry.archetypes.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "no enemy archetypes defined",
+            ));
+        }
+        Ok(registry)
+    }
+    pub fn load(path: &Path) -> io::Result<ArchetypeRegistry> {
+        ArchetypeRegistry::from_toml(&fs::read_to_string(path)?)
+    }
+    pub fn get(&self, name: &str) -> Option<&EnemyArchetype> {
+        self.archetypes
+            .iter()
+            .find(|archetype| archetype.name == name)
+    }
+    /// Picks an archetype unlocked by the given wave, weighted by spawn_weight
+    pub fn pick<R: Rng>(&self, wave: u32, rng: &mut R) -> &EnemyArchetype {
+        let unlocked: Vec<&EnemyArchetype> = self
+            .archetypes
+            .iter()
+            .filter(|archetype| archetype.first_wave <= wave.max(1))
+            .collect();
+        if unlocked.is_empty() {
+            // nothing unlocked yet, send in whatever unlocks first
+            return self
+                .archetypes
+                .iter()
+                .min_by_key(|archetype| archetype.first_wave)
+                .unwrap();
+        }
+        match WeightedIndex::new(unlocked.iter().map(|archetype| archetype.spawn_weight)) {
+            Ok(archetype_distribution) => unlocked[archetype_distribution.sample(rng)],
+            // every weight is zero, fall back to an even pick
+            Err(_) => unlocked[rng.gen_range(0..unlocked.len())],
+        }
+    }
+}
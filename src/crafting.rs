@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+use crate::main_gun::MainGun;
+use crate::map::Building;
+
+/// Shells the main gun can be loaded with
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ShellKind {
+    #[default]
+    Standard,
+    /// bigger blast that hits harder
+    HighExplosive,
+    /// weaker blast that keeps burning enemies inside it
+    Incendiary,
+}
+impl ShellKind {
+    pub fn next(&self) -> ShellKind {
+        match self {
+            ShellKind::Standard => ShellKind::HighExplosive,
+            ShellKind::HighExplosive => ShellKind::Incendiary,
+            ShellKind::Incendiary => ShellKind::Standard,
+        }
+    }
+    /// What a crafting station needs to make one of these
+    pub fn recipe(&self) -> Recipe {
+        match self {
+            ShellKind::Standard => Recipe {
+                material: 3,
+                processing_time: 4.,
+            },
+            ShellKind::HighExplosive => Recipe {
+                material: 8,
+                processing_time: 7.,
+            },
+            ShellKind::Incendiary => Recipe {
+                material: 5,
+                processing_time: 6.,
+            },
+        }
+    }
+    /// Multiplier on the main gun's damage
+    pub fn damage_multiplier(&self) -> f32 {
+        match self {
+            ShellKind::Standard => 1.,
+            ShellKind::HighExplosive => 1.5,
+            ShellKind::Incendiary => 0.5,
+        }
+    }
+    /// Multiplier on the main gun's blast radius
+    pub fn radius_multiplier(&self) -> f32 {
+        match self {
+            ShellKind::Standard => 1.,
+            ShellKind::HighExplosive => 1.4,
+            ShellKind::Incendiary => 1.,
+        }
+    }
+    /// Damage per second dealt to enemies standing in the blast while it burns
+    pub fn burn_damage(&self) -> f32 {
+        match self {
+            ShellKind::Incendiary => 12.,
+            _ => 0.,
+        }
+    }
+}
+/// Cost of crafting one shell
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Recipe {
+    /// material taken from deposited bodies, see `body_material`
+    pub material: u32,
+    /// seconds the station works on the shell
+    pub processing_time: f32,
+}
+/// Material a body is worth at a crafting station, bigger enemies are worth more
+pub fn body_material(size: f32) -> u32 {
+    ((size / 8.).round() as u32).max(1)
+}
+/// Workshop standing on a crafting station building, turning bodies into shells one at a time
+#[derive(Clone, Debug)]
+pub struct CraftingStation {
+    pub building_id: u32,
+    /// center of the building in screen space, where workers drop bodies off
+    pub position: (f32, f32),
+    /// shell started next
+    pub recipe: ShellKind,
+    /// material of each body waiting to be used
+    pub input_queue: VecDeque<u32>,
+    /// material already taken from the queue, left over from bodies that were worth more than needed
+    pub material: u32,
+    /// shell being made and the seconds spent on it
+    pub crafting: Option<(ShellKind, f32)>,
+    /// finished shell waiting for room in the main gun's loader
+    pub finished: Option<ShellKind>,
+}
+impl CraftingStation {
+    pub fn new(building_id: u32, building: &Building) -> Self {
+        CraftingStation {
+            building_id,
+            position: building.center(),
+            recipe: ShellKind::Standard,
+            input_queue: VecDeque::new(),
+            material: 0,
+            crafting: None,
+            finished: None,
+        }
+    }
+    /// Queues a deposited body of the given enemy size
+    pub fn deposit(&mut self, size: f32) {
+        self.input_queue.push_back(body_material(size));
+    }
+    /// Works on the current shell, starts the next one once enough bodies are queued and
+    /// hands finished shells to the main gun
+    pub fn update(&mut self, time_dif: f32, main_gun: &mut MainGun) {
+        if let Some(shell) = self.finished {
+            if !main_gun.load_shell(shell) {
+                return;
+            }
+            self.finished = None;
+        }
+        if self.crafting.is_none() {
+            let recipe = self.recipe.recipe();
+            while self.material < recipe.material {
+                let Some(material) = self.input_queue.pop_front() else {
+                    return;
+                };
+                self.material += material;
+            }
+            self.material -= recipe.material;
+            self.crafting = Some((self.recipe, 0.));
+        }
+        if let Some((shell, progress)) = &mut self.crafting {
+            *progress += time_dif;
+            if *progress >= shell.recipe().processing_time {
+                let shell = *shell;
+                self.crafting = None;
+                if !main_gun.load_shell(shell) {
+                    self.finished = Some(shell);
+                }
+            }
+        }
+    }
+}
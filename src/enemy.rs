@@ -1,179 +1,166 @@
-use ggez::graphics::{self, Canvas, Color, DrawParam, Mesh, MeshBuilder, Rect, Transform};
-use ggez::mint::Point2;
-use ggez::{Context, ContextBuilder, GameError, GameResult};
-use num::abs;
-use pathfinding::grid;
 use std::f32::consts::PI;
 
-use crate::{Direction, Map};
+use crate::archetype::EnemyArchetype;
+use crate::map::Map;
 
-const ORGIN: (f32, f32) = (0.0 as f32, 0.0 as f32);
-///ROTATION BASED ON TOP LEFT to BOTTOM RIGHT
-const ROTATION: [f32;4] =[PI,(3. * PI) / 2.,PI / 2., 0.0];
-//const ROTATION_BOTTOM: f32 = 0.0;
-// const ROTATION_TOP: f32 = PI;
-// const ROTATION_TOP_LEFT: f32 = (3. * PI) / 4.;
-// const ROTATION_TOP_RIGHT: f32 = (5. * PI) / 4.;
-// const ROTATION_Bottom_LEFT: f32 = PI / 4.;
-//const ROTATION_Bottom_RIGHT: f32 = (PI * 7.) / 4.;
-// const ROTATION_RIGHT: f32 = (3. * PI) / 2.;
-// const ROTATION_LEFT: f32 = PI / 2.;
-const STROKE_WIDTH: f32 = 2.0;
-const DIRECTIONS_REVERSED:[(f32,f32);4] = [(0.,-1.),(1.,0.),(-1.,0.),(0.,1.)];
+/// How fast an enemy can swing its heading around, in radians per second
+const TURN_SPEED: f32 = 3. * PI;
+pub const STROKE_WIDTH: f32 = 2.0;
 #[derive(Clone)]
 pub struct Enemy {
-    pub(crate) health: f32,
-    pub(crate) position: (f32, f32),
-    pub(crate) size: f32,
-    pub(crate) rotation: f32,
-    pub(crate) speed: u32,
-    pub(crate) building_hit: Option<u32>
+    /// handed out by the simulation when the enemy spawns, or on the next step for enemies pushed
+    /// onto its lists, the body keeps it so the main gun can lock on and workers can collect it
+    pub id: u32,
+    /// name of the archetype it was spawned from
+    pub kind: String,
+    pub health: f32,
+    pub position: (f32, f32),
+    pub size: f32,
+    /// triangle base as a fraction of size
+    pub width: f32,
+    pub color: [u8; 3],
+    pub rotation: f32,
+    pub speed: u32,
+    pub corpse_value: u32,
+    /// damage per second while attacking a building
+    pub attack_damage: f32,
+    /// building the enemy reached and is attacking
+    pub building_hit: Option<u32>,
 }
 pub struct Hitbox {
-    pub(crate) width: f32,
-    pub(crate) height: f32,
-    pub(crate) bottom_left: (f32, f32),
+    pub width: f32,
+    pub height: f32,
+    pub bottom_left: (f32, f32),
+}
+impl Hitbox {
+    pub fn contains(&self, position: (f32, f32)) -> bool {
+        position.0 > self.bottom_left.0
+            && position.0 < self.bottom_left.0 + self.width
+            && position.1 > self.bottom_left.1
+            && position.1 < self.bottom_left.1 + self.height
+    }
 }
 impl Enemy {
-    pub fn draw_dead(&mut self, ctx: &mut Context, canvas: &mut Canvas) {
-        let mut mesh_builder = MeshBuilder::new();
-        let half_size = self.size / 2.;
-        mesh_builder
-            .polygon(
-                graphics::DrawMode::stroke(STROKE_WIDTH),
-                &[
-                    [ORGIN.0 as f32, ORGIN.1 as f32 + self.size as f32],
-                    [ORGIN.0 as f32 - half_size, ORGIN.1 as f32 - self.size],
-                    [ORGIN.0 as f32 + half_size, ORGIN.1 as f32 - self.size],
-                ],
-                Color::from_rgb(139, 0, 0),
-            )
-            .unwrap();
-        let mesh_data = mesh_builder.build();
-        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
-        let current_position_point = Point2 {
-            x: self.position.0 as f32,
-            y: self.position.1 as f32,
-        };
-        canvas.draw(
-            &mesh,
-            DrawParam::default()
-                .rotation(self.rotation)
-                .dest(current_position_point),
-        );
+    pub fn from_archetype(archetype: &EnemyArchetype, position: (f32, f32), health: f32) -> Enemy {
+        Enemy {
+            id: 0,
+            kind: archetype.name.clone(),
+            health,
+            position,
+            size: archetype.size,
+            width: archetype.width,
+            color: archetype.color,
+            rotation: 0.0,
+            speed: archetype.speed,
+            corpse_value: archetype.corpse_value,
+            attack_damage: archetype.attack_damage,
+            building_hit: None,
+        }
     }
     pub fn get_hitbox(&self) -> Hitbox {
         Hitbox {
             width: (self.size + STROKE_WIDTH) * 2.5,
             height: (self.size + STROKE_WIDTH) * 2.5,
             bottom_left: (
-                self.position.0 as f32 - (self.size * 1.25),
-                self.position.1 as f32 - (self.size * 1.25),
+                self.position.0 - (self.size * 1.25),
+                self.position.1 - (self.size * 1.25),
             ),
         }
     }
-    pub fn draw_and_reach_base_check(&mut self, ctx: &mut Context, canvas: &mut Canvas, map: &Map) -> bool {
-        let mut current_rotation: f32 = 0.;
-        let mut mesh_builder = MeshBuilder::new();
-        let time_dif: f32 = ctx.time.delta().as_secs_f32();
-        let half_size = self.size / 2.;
-        //TODO: make sure this does not only cause damage to the main building, and affects the one where the grid is
-        let mut has_reached_objective = false;
-        let grid_position = Map::convert_position_to_grid_position(self.position);
-        let current_gridspace = &map.map[grid_position.0][grid_position.1];
-        if current_gridspace.building.is_some()
-        {
-            has_reached_objective = true;
+    /// Distance from the enemy's position to the furthest corner of its triangle, the base corners
+    /// sit `size * width` to either side of a point `size` behind it
+    pub fn body_radius(&self) -> f32 {
+        self.size * (1. + self.width * self.width).sqrt()
+    }
+    /// Corners of the triangle as it is drawn in screen space, the tip first
+    pub fn corners(&self) -> [(f32, f32); 3] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let half_width = self.size * self.width;
+        [
+            (0., self.size),
+            (-half_width, -self.size),
+            (half_width, -self.size),
+        ]
+        .map(|(x, y)| {
+            (
+                self.position.0 + x * cos - y * sin,
+                self.position.1 + x * sin + y * cos,
+            )
+        })
+    }
+    /// Distance from a screen space point to the nearest part of the triangle, 0 inside it
+    pub fn distance_to_body(&self, point: (f32, f32)) -> f32 {
+        let corners = self.corners();
+        let mut sides = [0.; 3];
+        let mut nearest = f32::MAX;
+        for index in 0..3 {
+            let start = corners[index];
+            let end = corners[(index + 1) % 3];
+            let edge = (end.0 - start.0, end.1 - start.1);
+            let to_point = (point.0 - start.0, point.1 - start.1);
+            sides[index] = edge.0 * to_point.1 - edge.1 * to_point.0;
+            let length_squared = edge.0 * edge.0 + edge.1 * edge.1;
+            let along =
+                ((edge.0 * to_point.0 + edge.1 * to_point.1) / length_squared).clamp(0., 1.);
+            let dif_x = to_point.0 - edge.0 * along;
+            let dif_y = to_point.1 - edge.1 * along;
+            nearest = nearest.min((dif_x * dif_x + dif_y * dif_y).sqrt());
         }
-        // UPDATING POSITION 
-        if let Some(direction) = current_gridspace.direction.clone(){
-            println!("direction found: {}",direction.clone() as usize);
-            println!("speed: {}",self.speed);
-            println!("timedif: {}",time_dif);
-            println!("ammount of movement {}",(DIRECTIONS_REVERSED[direction.clone() as usize].0 as f32 * self.speed as f32 * time_dif) as  f32);
-            self.position.0 = self.position.0 + (DIRECTIONS_REVERSED[direction.clone() as usize].0 as f32 * self.speed as f32 * time_dif);
-            self.position.1 = self.position.1 + (DIRECTIONS_REVERSED[direction as usize].1 as f32 * self.speed as f32 * time_dif);
-        }else{
-            println!("direction does not exist");
+        // on the same side of every edge means the point is inside
+        if sides.iter().all(|side| *side >= 0.) || sides.iter().all(|side| *side <= 0.) {
+            return 0.;
         }
-        // build the triangle around the new point
-        mesh_builder
-            .polygon(
-                graphics::DrawMode::fill(),
-                &[
-                    [ORGIN.0 as f32, ORGIN.1 as f32 + self.size as f32],
-                    [ORGIN.0 as f32 - half_size, ORGIN.1 as f32 - self.size],
-                    [ORGIN.0 as f32 + half_size, ORGIN.1 as f32 - self.size],
-                ],
-                Color::RED,
-            )
-            .unwrap();
-        let mesh_data = mesh_builder.build();
-        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
-        // start
-        //UPDATING ROTATION
-        if let Some(direction) = current_gridspace.direction.clone(){
-            //const rotation_bottom = 0.0;
-            //const rotation_top_rotaiton = PI;
-            //const rotation_top_left = (3. * PI) / 4.;
-            //const rotation_top_right = (5. * PI) / 4.;
-            //const rotation_bottom_left = PI /4.;
-            //const rotation_bottom?_right = (PI * 7.) /4.;
-            //const rotation_right = (3. * PI) / 2.;
-            //const rotation_left = PI /2.;
-            current_rotation = ROTATION[direction as usize];
-            // BASED ON OLD ASTAR ARRAY
-            //let corrected_x = self.position.0;
-            //let corrected_y = -self.position.1;
-            //let corrected_next_x = next_x;
-            //let corrected_next_y = -next_y;
-            // if corrected_x < corrected_next_x {
-            //     if corrected_y < corrected_next_y {
-            //         // looking up right
-            //         current_rotation = ROTATION_TOP_RIGHT;
-            //     } else if corrected_y > corrected_next_y {
-            //         // looking down right
-            //         current_rotation = ROTATION_Bottom_RIGHT;
-            //     } else {
-            //         //looking right
-            //         current_rotation = ROTATION_RIGHT;
-            //     }
-            // } else if corrected_x > corrected_next_x {
-            //     // looking left
-            //     if corrected_y < corrected_next_y {
-            //         // looking up left
-            //         current_rotation = ROTATION_TOP_LEFT;
-            //     } else if corrected_y > corrected_next_y {
-            //         // looking down left
-            //         current_rotation = ROTATION_Bottom_LEFT;
-            //     } else {
-            //         // looking left
-            //         current_rotation = ROTATION_LEFT;
-            //     }
-            // } else {
-            //     if corrected_y < corrected_next_y {
-            //         // looking up
-            //         current_rotation = ROTATION_TOP;
-            //     } else {
-            //         // looking down
-            //         current_rotation = ROTATION_BOTTOM;
-            //     }
-            // }
-        }else if let Some(building_grid_info) = current_gridspace.building.clone(){
+        nearest
+    }
+    /// Rotation of the triangle pointing along a screen space heading, 0 faces down the screen
+    pub fn rotation_of(heading: (f32, f32)) -> f32 {
+        (-heading.0).atan2(heading.1).rem_euclid(2. * PI)
+    }
+    /// Unit screen space heading for a rotation, the inverse of `rotation_of`
+    pub fn heading_of(rotation: f32) -> (f32, f32) {
+        (-rotation.sin(), rotation.cos())
+    }
+    /// Screen space velocity the flow path is carrying the enemy at, still while it attacks a building
+    pub fn velocity(&self, map: &Map) -> (f32, f32) {
+        let grid_position = Map::convert_position_to_grid_position(self.position);
+        let current_gridspace = &map.map[grid_position.0][grid_position.1];
+        let Some(direction) = current_gridspace
+            .direction
+            .as_ref()
+            .filter(|_| current_gridspace.building.is_none())
+        else {
+            return (0., 0.);
+        };
+        let (flow_x, flow_y) = direction.heading();
+        let speed = self.speed as f32 * current_gridspace.terrain.speed_ratio();
+        (flow_x * speed, flow_y * speed)
+    }
+    /// Moves the enemy along the flow path for one simulation step
+    ///
+    /// Reaching the edge of any building stops the enemy there with `building_hit` set until the building falls
+    pub fn update(&mut self, time_dif: f32, map: &Map) {
+        let grid_position = Map::convert_position_to_grid_position(self.position);
+        let current_gridspace = &map.map[grid_position.0][grid_position.1];
+        self.building_hit = None;
+        if let Some(building_grid_info) = &current_gridspace.building {
             self.building_hit = Some(building_grid_info.id);
-            return true;
+            return;
         }
-        let current_position_point = Point2 {
-            x: self.position.0 as f32,
-            y: self.position.1 as f32,
-        };
-        self.rotation = current_rotation;
-        canvas.draw(
-            &mesh,
-            DrawParam::default()
-                .rotation(current_rotation)
-                .dest(current_position_point),
-        );
-        has_reached_objective
+        // UPDATING POSITION AND ROTATION
+        // steer towards the flow of the current space and always walk where the enemy is facing,
+        // so corners are rounded off instead of snapping between headings
+        if let Some(direction) = current_gridspace.direction.clone() {
+            let (flow_x, flow_y) = direction.heading();
+            let wanted_rotation = Enemy::rotation_of((flow_x, flow_y));
+            // shortest way round, wrapped to -PI..PI
+            let turn = (wanted_rotation - self.rotation + PI).rem_euclid(2. * PI) - PI;
+            let max_turn = TURN_SPEED * time_dif;
+            self.rotation = (self.rotation + turn.clamp(-max_turn, max_turn)).rem_euclid(2. * PI);
+        }
+        let speed = self.speed as f32 * current_gridspace.terrain.speed_ratio();
+        let (heading_x, heading_y) = Enemy::heading_of(self.rotation);
+        // a lagging heading can carry a fast enemy past the border, keep it on the map
+        self.position.0 = (self.position.0 + heading_x * speed * time_dif).clamp(-500., 500.);
+        self.position.1 = (self.position.1 + heading_y * speed * time_dif).clamp(-500., 500.);
     }
 }
@@ -0,0 +1,15 @@
+//! Gameplay core of The Last Hope, steppable without a window or graphics context
+pub mod archetype;
+pub mod crafting;
+pub mod enemy;
+pub mod main_gun;
+pub mod map;
+pub mod pathing;
+pub mod replay;
+pub mod sentry;
+pub mod simulation;
+pub mod spatial;
+pub mod wave;
+pub mod worker;
+
+pub use simulation::{Command, LossConditions, LossReason, Simulation};
@@ -1,27 +1,66 @@
-use enemy::Enemy;
 use ggez::event::{self, EventHandler, MouseButton};
-use ggez::graphics::{
-    self, Canvas, Color, DrawParam, Drawable, Mesh, MeshBuilder, PxScale, Rect, Text, TextFragment,
-};
-use ggez::input::keyboard::{self, KeyInput};
+use ggez::graphics::{self, Canvas, Color, DrawParam, Drawable, PxScale, Rect, Text, TextFragment};
 use ggez::winit::event::VirtualKeyCode;
 use ggez::{conf, Context, ContextBuilder, GameError, GameResult};
-use libm::atan2f;
-use num::cast::AsPrimitive;
-use pathfinding::matrix::directions;
-use pathfinding::prelude::astar;
-use rand::Rng;
+use render::building::{self as building_render, BuildingRender};
+use render::crafting::CraftingStationRender;
+use render::enemy::EnemyRender;
+use render::main_gun::MainGunRender;
+use render::sentry::SentryRender;
+use render::worker::{self as worker_render, WorkerRender};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use the_last_hope::archetype::{ArchetypeRegistry, ARCHETYPE_PATH};
+use the_last_hope::map::BuildingType;
+use the_last_hope::replay::{Replay, ReplayPlayer};
+use the_last_hope::worker::WORKER_HIRE_COST;
+use the_last_hope::{Command, LossConditions, LossReason, Simulation};
 const DEFAULT_CAM_SIZE: f32 = 100.0;
-use std::any::Any;
-use std::default;
-use std::f32::consts::PI;
-use std::ops::{Div, Mul};
-mod enemy;
-mod main_gun;
-mod worker;
-use std::collections::{HashMap, VecDeque};
-const DIRECTIONS:[(i32,i32);4] = [(0,1),(-1,0),(1,0),(0,-1)];
+const TICKS_PER_SECOND: u32 = 60;
+const TICK_DURATION: f32 = 1. / TICKS_PER_SECOND as f32;
+mod render;
+/// Options read from the command line
+#[derive(Default)]
+struct Args {
+    /// seed for the first game, a random one is rolled when missing
+    seed: Option<u64>,
+    /// replay file to watch instead of playing
+    replay: Option<PathBuf>,
+    loss_conditions: LossConditions,
+}
+impl Args {
+    fn parse() -> Self {
+        let mut args = Args::default();
+        let mut raw_args = std::env::args().skip(1);
+        while let Some(arg) = raw_args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let seed = raw_args.next().expect("--seed needs a number after it");
+                    args.seed = Some(seed.parse().expect("--seed needs a whole number"));
+                }
+                "--replay" => {
+                    let path = raw_args.next().expect("--replay needs a file after it");
+                    args.replay = Some(PathBuf::from(path));
+                }
+                "--lose-without-sentries" => args.loss_conditions.all_sentries_lost = true,
+                "--lose-after-buildings" => {
+                    let limit = raw_args
+                        .next()
+                        .expect("--lose-after-buildings needs a number after it");
+                    args.loss_conditions.buildings_lost = Some(
+                        limit
+                            .parse()
+                            .expect("--lose-after-buildings needs a whole number"),
+                    );
+                }
+                _ => println!("ignoring unknown argument {}", arg),
+            }
+        }
+        args
+    }
+}
 fn main() {
+    let args = Args::parse();
     // Make a Context.
     let mut cf = conf::Conf::new();
     cf.window_setup.title = "The Last Hope".to_owned();
@@ -32,170 +71,11 @@ fn main() {
     // Create an instance of your event handler.
     // Usually, you should provide it with the Context object to
     // use when setting your game up.
-    let my_game = MyGame::new(&mut ctx);
+    let my_game = MyGame::new(&mut ctx, args);
     // Run!
     event::run(ctx, event_loop, my_game);
 }
-#[derive(Clone,Copy)]
-enum BuildingType{
-    Sentry,
-    Baricade,
-}
-#[derive(Clone)]
-enum Direction{
-    TOP,
-    LEFT,
-    RIGHT,
-    BOTTOM
-}
-impl Direction {
-    fn new(index:usize) -> Self{
-        match index{
-            0=> Direction::TOP,
-            1=> Direction::LEFT,
-            2=> Direction::RIGHT,
-            3=> Direction::BOTTOM,
-            _=> panic!("Used an index outside of possible Directions")
-        }
-    }
-}
-#[derive(Clone)]
-struct BuildingGridInfo{
-    id: u32,
-    typ: BuildingType,
-}
-///Will either be used to direct enemies or assign damage if an enemy attacks it
-#[derive(Clone)]
-struct GridSpace{
-    building: Option<BuildingGridInfo>,
-    direction: Option<Direction>
-}
-#[derive(Clone)]
-struct Map {
-    map: Vec<Vec<GridSpace>>,
-}
-///Grid system for placing objects and pathing enemies
-impl Map {
-    fn new(building_hash_map: &HashMap<u32,Building>) -> Self{
-        let empty_grid_space: GridSpace  = GridSpace { building:None,direction:None };
-         let mut default_map = vec![vec![empty_grid_space;251];251];
-         //add any existing buildings before initial map build
-         for (current_building_id, building) in building_hash_map{
-            for y in building.bottom_left.1..building.bottom_left.1-building.height{
-                for x in building.bottom_left.0..building.bottom_left.0+building.width{
-                    default_map[x][y].building = Some(BuildingGridInfo {id: *current_building_id, typ: building.building_type })
-                }
-            }
-         }
-         Map{ map: default_map}
-    }
-    //TODO: add checks for if building is being built inside another building
-    pub fn add_building(&mut self, building_id:u32, building:Building){
-        for y in building.bottom_left.1..building.bottom_left.1-building.height{
-            for x in building.bottom_left.0..building.bottom_left.0+building.width{
-                self.map[x][y].building = Some(BuildingGridInfo {id: building_id, typ: building.building_type })
-            }
-        }
-    }
-    ///used to convert -500.0 to 500.0 to the grid system of 0 to 250 lossfully
-    pub fn convert_position_to_grid_position(position:(f32,f32)) -> (usize,usize){
-        let new_position:(f32,f32)= (((position.0+500.)/4.), ((position.1+500.)/4.));
-        let output:(usize,usize)= (new_position.0.as_(), new_position.1.as_());
-        println!("converted ({},{}) to ({},{}) to ({},{})",position.0,position.1,new_position.0,new_position.1,output.0,output.1);
-        return output;
-    }
-    //Path system built into the grid system that priorizies nearest objective
-    pub fn build_flow_path(&mut self, building_hash_map:&HashMap<u32,Building>){
-        // do a level pathing off off each building position
-        // steps:
-        // build a double sidded queue
-        println!("Path being built");
-        let mut spread_queue:VecDeque<(usize,usize)> = VecDeque::new();
-        // TODO: double check this beecause all of the paths seems to only have to points of orgins and also add it so there is a direction on this
-        // feed the queue all outside layers of buildings, x lines take priority over corners to avoid wasted time with duplication
-        for (_, building) in building_hash_map{
-            // |__
-            for y in building.bottom_left.1-1..building.bottom_left.1-building.height+1{
-                spread_queue.push_back((building.bottom_left.0,y));
-            }
-            for x in building.bottom_left.0..building.bottom_left.0+building.width{
-                spread_queue.push_back((x,building.bottom_left.1));
-            }
-            //  __
-            //    |
-            for y in building.bottom_left.1-1..building.bottom_left.1-building.height+1{
-                spread_queue.push_back((building.bottom_left.0+building.width,y));
-            }
-            for x in building.bottom_left.0..building.bottom_left.0+building.width{
-                spread_queue.push_back((x,building.bottom_left.1-building.height));
-            }
-        }
-        // now MAKE IT SPREAD
-        while !spread_queue.is_empty(){
-            // pop the current space
-            let current_location = spread_queue.pop_front().unwrap();
-            // give all surrounding gridspaces that do not have (directions and building) directions to the current space
-            for (index, direction) in DIRECTIONS.iter().enumerate(){
-                //direction is off the grid
-                if (direction.0 + current_location.0 as i32)<0 || (direction.0 + current_location.0 as i32) >250{
-                    println!("fail 1");
-                    continue;
-                } 
-                
-                if (direction.1 + current_location.1 as i32) < 0 || (direction.1 + current_location.1 as i32) > 250{
-                    println!("fail 2");
-                    continue;
-                }
-                
-                let current_surrounding_space_cord:(usize,usize)  = 
-                    (usize::try_from(current_location.0 as i32+direction.0).unwrap(),usize::try_from(current_location.1 as i32+direction.1).unwrap());
-                let current_surrounding_space:&mut GridSpace = 
-                    &mut self.map[ current_surrounding_space_cord.0][current_surrounding_space_cord.1];
-                if current_surrounding_space.building.is_none() && current_surrounding_space.direction.is_none(){
-                    current_surrounding_space.direction = Some(Direction::new(index));
-                    spread_queue.push_back(current_surrounding_space_cord);
-                    println!("direction added")
-                }
-            }  
-            //OR just feed the direction like i already set up to the directions that dont have a building and directions
-        
-        }
-        // add the new spaces to the queue
-        println!("Path finished being built");
-    }
-    // pub fn find_moveable_options(&self, x: i32, y: i32) -> Vec<(((i32, i32) u32))> {
-    //     let movement_ammount = 5;
-    //     let mut movement_options: Vec<((i32, i32),u32)> = vec![
-    //         ((x + movement_ammount, y + movement_ammount),0),
-    //         ((x + movement_ammount, y),0),
-    //         ((x + movement_ammount, y - movement_ammount),0),
-    //         ((x, y + movement_ammount),0),
-    //         ((x, y - movement_ammount),0),
-    //         ((x - movement_ammount, y + movement_ammount),0),
-    //         ((x - movement_ammount, y),0),
-    //         ((x - movement_ammount, y - movement_ammount),0),
-    //     ];
-    //     let mut index = 7;
-    //     loop {
-    //         let current_cord = movement_options[index].0;
-    //         if current_cord.0 > 500
-    //             || current_cord.1 > 500
-    //             || current_cord.0 < -500
-    //             || current_cord.1 < -500
-    //         {
-    //             // out of grid
-    //         } else if self.map[(current_cord.0 + 500) as usize][(current_cord.1 + 500) as usize].is_some() {
-    //             //need to it to alter the movement cost (+ for senteries, - for )
-    //             movement_options.remove(index);
-    //         }
-    //         if index == 0 {
-    //             break;
-    //         }
-    //         index -= 1;
-    //     }
-    //     movement_options
-    // }
-}
+#[allow(dead_code)]
 enum State {
     StartMenu,
     Playing,
@@ -204,189 +84,189 @@ enum State {
 }
 struct MyGame {
     state: State,
-    current_game: Game,
-}
-//TODO: ADD more details to add variance for other buildings besides barriers
-struct Building{
-    building_type: BuildingType,
-    bottom_left: (usize,usize),
-    width: usize,
-    height: usize,
-    max_health: f32,
-    health: f32
-}
-struct Game {
-    main_gun: main_gun::MainGun,
-    map: Map,
-    path_built:bool,
-    last_building_added_id: u32,
-    building_hash_map: HashMap<u32,Building>,
-    enemy_alive_list: Vec<enemy::Enemy>,
-    enemy_dead_list: Vec<enemy::Enemy>,
-    worker_task_list: Vec<worker::Task>,
-    worker_list: Vec<worker::Worker>,
-    rooftop_view: bool,
+    simulation: Simulation,
+    seed: Option<u64>,
+    /// enemy types handed to every new game, read from the archetype data file
+    archetypes: ArchetypeRegistry,
+    loss_conditions: LossConditions,
+    /// loaded from --replay, every game started replays it instead of taking input
+    replay: Option<Replay>,
+    replay_player: Option<ReplayPlayer>,
+    /// where the current game is being recorded to
+    replay_path: Option<PathBuf>,
+    saved_command_count: Option<usize>,
     camera_zoom_ratio: f32,
-    shake_meter: u8,
     camera_x: f32,
     camera_y: f32,
+    /// building picked for placement in the ground view, None when not placing
+    placing: Option<BuildingType>,
+    placement_rotated: bool,
+    /// last known mouse position in screen pixels
+    mouse_position: [f32; 2],
 }
-impl Default for Game {
-    fn default() -> Self {
-        //The health for the player/ main building/ main gun
-            // width = 18
-            // height =  12
-            // bottom left x = 116
-            // bottom left y = 119
-        let mut building_hash_map:HashMap<u32,Building>= HashMap::new();
-        let main_building = Building{ bottom_left: (134,131), width: 18, height: 12, max_health: 1000., health: 1000.,building_type:BuildingType::Sentry };
-        building_hash_map.insert(0, main_building);
-        Game {
 
-            worker_list: Vec::new(),
-            worker_task_list: Vec::new(),
-            last_building_added_id: 0,
-            map: Map::new(&building_hash_map),
-            path_built: false,
-            building_hash_map,
-            main_gun: main_gun::MainGun {
-                shooting_duration: 2.,
-                enabled: true,
-                rotation_speed_per_second: PI / 10.0,
-                shell_explosive_radius: 50.,
-                damage: 100.,
-                ..Default::default()
-            },
-            enemy_alive_list: Vec::new(),
-            enemy_dead_list: Vec::new(),
-            rooftop_view: true,
+impl MyGame {
+    pub fn new(_ctx: &mut Context, args: Args) -> MyGame {
+        let replay = args.replay.map(|path| {
+            Replay::load(&path).unwrap_or_else(|error| {
+                panic!("could not read replay {}: {}", path.display(), error)
+            })
+        });
+        let archetype_path = Path::new(ARCHETYPE_PATH);
+        let archetypes = if archetype_path.exists() {
+            ArchetypeRegistry::load(archetype_path).unwrap_or_else(|error| {
+                panic!("could not read {}: {}", archetype_path.display(), error)
+            })
+        } else {
+            ArchetypeRegistry::default()
+        };
+        let mut my_game = MyGame {
+            state: State::StartMenu,
+            simulation: Simulation::new(),
+            seed: args.seed,
+            archetypes,
+            loss_conditions: args.loss_conditions,
+            replay,
+            replay_player: None,
+            replay_path: None,
+            saved_command_count: None,
             camera_zoom_ratio: 1.,
-            shake_meter: 0,
             camera_x: -50.,
             camera_y: -50.,
+            placing: None,
+            placement_rotated: false,
+            mouse_position: [0., 0.],
+        };
+        my_game.reset();
+        my_game
+    }
+    /// Resets the variables in preperation for next game
+    pub fn reset(&mut self) {
+        self.state = State::StartMenu;
+        if let Some(replay) = &self.replay {
+            let replay_player = ReplayPlayer::new(replay.clone());
+            self.simulation = replay_player.new_simulation();
+            self.replay_player = Some(replay_player);
+            println!("Replaying game with seed {}", self.simulation.seed);
+        } else {
+            self.simulation = match self.seed {
+                Some(seed) => Simulation::with_seed(seed),
+                None => Simulation::new(),
+            };
+            let started = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default();
+            self.replay_path = Some(PathBuf::from(format!(
+                "replays/{}-{}.replay",
+                self.simulation.seed, started
+            )));
+            println!("Starting game with seed {}", self.simulation.seed);
         }
+        self.simulation.archetypes = self.archetypes.clone();
+        self.simulation.loss_conditions = self.loss_conditions.clone();
+        self.saved_command_count = None;
+        self.save_replay();
+        self.camera_zoom_ratio = 1.;
+        self.camera_x = -50.;
+        self.camera_y = -50.;
+        self.placing = None;
+        self.placement_rotated = false;
     }
-}
-///position used for defining a place for pathing
-struct Pos {
-    bottom_left: (i32,i32),
-    scale: (i32,i32),
-    center: (i32,i32)
-}
-impl Pos{
-    fn new(bottom_left: (i32, i32),scale: (i32,i32)) -> Self{
-        Pos{ bottom_left, scale, center: (bottom_left.0 + (scale.0/2),bottom_left.1 + (scale.1/2)) }
+    /// Rewrites the replay file whenever new commands were applied so a crash still leaves it behind
+    fn save_replay(&mut self) {
+        let Some(replay_path) = &self.replay_path else {
+            return;
+        };
+        let command_count = self.simulation.replay.commands.len();
+        if self.saved_command_count == Some(command_count) {
+            return;
+        }
+        match self.simulation.replay.save(replay_path) {
+            Ok(()) => self.saved_command_count = Some(command_count),
+            Err(error) => println!("could not save replay {}: {}", replay_path.display(), error),
+        }
     }
-    fn distance_to_center (&self, given_point:(i32,i32)) -> u32{
-        self.center.0.abs_diff(given_point.0) + self.center.1.abs_diff(given_point.1)
+    /// Player commands are ignored while watching a replay
+    fn queue_command(&mut self, command: Command) {
+        if self.replay_player.is_none() {
+            self.simulation.queue_command(command);
+        }
     }
-}
-
-impl MyGame {
-    pub fn new(_ctx: &mut Context) -> MyGame {
-        MyGame {
-            state: State::StartMenu,
-            current_game: Default::default(),
+    /// Command that would place the picked building under the mouse, snapped to the grid
+    fn placement_command(&self, ctx: &Context) -> Option<Command> {
+        let building_type = self.placing?;
+        let [x, y] = self.screen_cord_to_world_cord(ctx, &self.mouse_position);
+        // grid spaces are 4 units wide and use screen space like the enemies
+        let grid_x = ((x + 500.) / 4.).floor() as i32;
+        let grid_y = ((-y + 500.) / 4.).floor() as i32;
+        let (width, height) = self.placement_footprint(building_type);
+        Some(Command::PlaceBuilding {
+            building_type,
+            x: grid_x - width as i32 / 2,
+            y: grid_y + (height as i32 + 1) / 2,
+            rotated: self.placement_rotated,
+        })
+    }
+    fn placement_footprint(&self, building_type: BuildingType) -> (usize, usize) {
+        let (width, height) = building_type.footprint();
+        if self.placement_rotated {
+            (height, width)
+        } else {
+            (width, height)
         }
     }
-    /// Resets the variables in preperation for next game
-    pub fn reset(&mut self) {
-        self.state = State::StartMenu;
-        self.current_game = Default::default();
+    fn draw_sentries(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        let mut sentry_ids: Vec<&u32> = self.simulation.sentry_hash_map.keys().collect();
+        sentry_ids.sort();
+        for sentry_id in sentry_ids {
+            self.simulation.sentry_hash_map[sentry_id].draw(ctx, canvas);
+        }
     }
-    fn add_building(){
-        //TODO: ensure buildings dont overlap, use the id based on the last id in the game object +1
+    fn draw_crafting_stations(&self, canvas: &mut Canvas) {
+        let mut crafting_ids: Vec<&u32> = self.simulation.crafting_hash_map.keys().collect();
+        crafting_ids.sort();
+        for crafting_id in crafting_ids {
+            self.simulation.crafting_hash_map[crafting_id].draw(canvas);
+        }
     }
-    fn spawn_enemy(&mut self) -> Result<(), GameError> {
-        let random_ratio: f32 = rand::thread_rng().gen_range(0.0..1.);
-        let base_health: f32 = 100. * (random_ratio + 0.5);
-        let base_size = 20. * (random_ratio + 0.5);
-        let random_side: i8 = rand::thread_rng().gen_range(1..5);
-        let mut random_side_length = 500. * random_ratio;
-        if rand::random() {
-            random_side_length = random_side_length * -1.;
+    fn draw_buildings(&self, ctx: &Context, canvas: &mut Canvas) {
+        let mut building_ids: Vec<&u32> = self.simulation.building_hash_map.keys().collect();
+        building_ids.sort();
+        for rubble in &self.simulation.rubble_list {
+            rubble.draw(canvas);
+        }
+        // the main building is the floor drawn under everything
+        for building_id in building_ids.into_iter().filter(|id| **id != 0) {
+            self.simulation.building_hash_map[building_id].draw(canvas);
+        }
+        if let Some(Command::PlaceBuilding {
+            building_type,
+            x,
+            y,
+            rotated,
+        }) = self.placement_command(ctx)
+        {
+            let valid = self
+                .simulation
+                .plan_building(building_type, (x, y), rotated)
+                .is_ok();
+            let footprint = self.placement_footprint(building_type);
+            building_render::draw_ghost(canvas, (x, y), footprint, valid);
         }
-        println!("current side: {}", random_side);
-        let position_generated: (f32, f32) = match random_side {
-            1 => (-500., random_side_length),
-            2 => (500., random_side_length),
-            3 => (random_side_length, -500.),
-            4 => (random_side_length, 500.),
-            _ => {
-                panic!()
-            }
-        };
-        println!(
-            "Enemy Spawned at {},{} with size {} with already {} enemies",
-            &position_generated.0,
-            &position_generated.1,
-            &base_size,
-            self.current_game.enemy_alive_list.len()
-        );
-        let new_enemy = Enemy {
-            health: base_health,
-            size: base_size,
-            position: position_generated,
-            speed: 15,
-            rotation: 0.0,
-            building_hit: None,
-        };
-        //Path has been moved onto the grid spaces
-        //println!("Path building started");
-        //new_enemy.path = self.build_path(position_generated, (0, 0)).unwrap().0;
-        //println!("Path building finished");
-        self.current_game.enemy_alive_list.push(new_enemy);
-        Ok(())
     }
-    //TODO optimize to the grid and not pizels so that workers can use this to reach dead bodies
-    // fn build_path(
-    //     &self,
-    //     start_location: (i32, i32),
-    //     goal_bottom_left: (i32, i32),
-    //     goal_scale: (i32, i32)
-    // ) -> Option<(Vec<(i32, i32)>, u32)> {
-    //     let goal_limit = (goal_bottom_left.0 + goal_scale.0, goal_bottom_left.1 + goal_scale.1);
-    //     let distance_x = 
-    //     let result: Option<(Vec<(i32, i32)>, u32)> = astar(
-    //         &start_location,
-    //         |&(x, y)| {
-    //             self.current_game
-    //                 .map
-    //                 .find_moveable_options(x, y)
-    //         },
-    //         |&(x, y)| (goal.0.abs_diff(x) + goal.1.abs_diff(y)) / 3,
-    //         |&(x,y)| x > ,
-    //     );
-    //     result
-    // }
     fn offset_to_screen_cord(&self, ctx: &Context, screen_cord_wanted: &[f32; 2]) -> [f32; 2] {
         let window = ctx.gfx.window();
         let window_size = window.inner_size();
         let aspect_ratio = window_size.width as f32 / window_size.height as f32;
-        let camera_world_view_width =
-            DEFAULT_CAM_SIZE * self.current_game.camera_zoom_ratio * aspect_ratio;
-        let camera_world_view_height = DEFAULT_CAM_SIZE * self.current_game.camera_zoom_ratio;
+        let camera_world_view_width = DEFAULT_CAM_SIZE * self.camera_zoom_ratio * aspect_ratio;
+        let camera_world_view_height = DEFAULT_CAM_SIZE * self.camera_zoom_ratio;
         let cord_wanted_x = screen_cord_wanted[0] * camera_world_view_width;
         let cord_wanted_y = screen_cord_wanted[1] * camera_world_view_height;
-        let output_x = cord_wanted_x + (self.current_game.camera_x * aspect_ratio);
-        let output_y = cord_wanted_y + self.current_game.camera_y;
+        let output_x = cord_wanted_x + (self.camera_x * aspect_ratio);
+        let output_y = cord_wanted_y + self.camera_y;
         [output_x, output_y]
     }
-    fn generate_shake_offset(&mut self) -> (f32, f32) {
-        if self.current_game.shake_meter > 0 {
-            let mut rng = rand::thread_rng();
-            let current_shake_meter: f32 = self.current_game.shake_meter as f32;
-            let gen_x: f32 = rng.gen();
-            let gen_y: f32 = rng.gen();
-            self.current_game.shake_meter -= 1;
-            return (
-                (gen_x * current_shake_meter) - (current_shake_meter / 2.),
-                (gen_y * current_shake_meter) - (current_shake_meter / 2.),
-            );
-        } else {
-            return (0., 0.);
-        }
-    }
     fn screen_cord_to_world_cord(&self, ctx: &Context, screen_cord: &[f32; 2]) -> [f32; 2] {
         // scale our x and y from [0, screen_width] to [0, 1]
         let window = ctx.gfx.window();
@@ -398,113 +278,81 @@ impl MyGame {
         ];
 
         // convert our NDC into world space
-        let camera_world_view_width = DEFAULT_CAM_SIZE * self.current_game.camera_zoom_ratio;
-        let camera_world_view_height = DEFAULT_CAM_SIZE * self.current_game.camera_zoom_ratio;
-        let world_coord = [
-            ndc[0] * camera_world_view_width * aspect_ratio
-                + (self.current_game.camera_x * aspect_ratio),
-            (ndc[1] * camera_world_view_height + self.current_game.camera_y) * -1.,
-        ];
-
-        println!("Converted {:?} to {:?}", screen_cord, world_coord);
-        world_coord
-    }
-    fn switch_perspective(&mut self) -> Result<(), GameError> {
-        self.current_game.rooftop_view = !self.current_game.rooftop_view;
-        self.current_game.main_gun.enabled = self.current_game.rooftop_view;
-        Ok(())
+        let camera_world_view_width = DEFAULT_CAM_SIZE * self.camera_zoom_ratio;
+        let camera_world_view_height = DEFAULT_CAM_SIZE * self.camera_zoom_ratio;
+        [
+            ndc[0] * camera_world_view_width * aspect_ratio + (self.camera_x * aspect_ratio),
+            -(ndc[1] * camera_world_view_height + self.camera_y),
+        ]
     }
     fn change_camera_zoom(&mut self, zoom_increase: bool) -> Result<(), GameError> {
         let diffrence_in_cam_size = DEFAULT_CAM_SIZE * 0.1;
         if zoom_increase {
             //increase zoom
-            let new_camera_zoom_ratio = self.current_game.camera_zoom_ratio - 0.1;
+            let new_camera_zoom_ratio = self.camera_zoom_ratio - 0.1;
             if new_camera_zoom_ratio > 0.5 {
-                self.current_game.camera_zoom_ratio = new_camera_zoom_ratio;
-                self.current_game.camera_x += diffrence_in_cam_size / 2.;
-                self.current_game.camera_y += diffrence_in_cam_size / 2.;
+                self.camera_zoom_ratio = new_camera_zoom_ratio;
+                self.camera_x += diffrence_in_cam_size / 2.;
+                self.camera_y += diffrence_in_cam_size / 2.;
             }
         } else {
             //decrease zoom
-            self.current_game.camera_zoom_ratio += 0.1;
-            self.current_game.camera_x -= diffrence_in_cam_size / 2.;
-            self.current_game.camera_y -= diffrence_in_cam_size / 2.;
+            self.camera_zoom_ratio += 0.1;
+            self.camera_x -= diffrence_in_cam_size / 2.;
+            self.camera_y -= diffrence_in_cam_size / 2.;
         }
-        println!("Current Zoom:{}", self.current_game.camera_zoom_ratio);
+        println!("Current Zoom:{}", self.camera_zoom_ratio);
         Ok(())
     }
     fn change_camera_location(&mut self, key_pressed: char) -> Result<(), GameError> {
         match key_pressed {
-            'w' => self.current_game.camera_y -= 2. * self.current_game.camera_zoom_ratio,
-            'a' => self.current_game.camera_x -= 2. * self.current_game.camera_zoom_ratio,
-            's' => self.current_game.camera_y += 2. * self.current_game.camera_zoom_ratio,
-            'd' => self.current_game.camera_x += 2. * self.current_game.camera_zoom_ratio,
+            'w' => self.camera_y -= 2. * self.camera_zoom_ratio,
+            'a' => self.camera_x -= 2. * self.camera_zoom_ratio,
+            's' => self.camera_y += 2. * self.camera_zoom_ratio,
+            'd' => self.camera_x += 2. * self.camera_zoom_ratio,
             _ => (),
         }
         Ok(())
     }
-    fn manage_enemies(&mut self, ctx: &mut Context, canvas: &mut Canvas) {
-        if self.current_game.enemy_alive_list.len() != 0 {
-            let mut current_enemy_index: usize = self.current_game.enemy_alive_list.len() - 1;
-            //check enemies for abnomalities and spawn
-            loop {
-                let current_enemy = &mut self.current_game.enemy_alive_list[current_enemy_index];
-                if current_enemy.health < 0. {
-                    //put the dead enemies in the deadlist
-                    self.current_game.enemy_dead_list.push(
-                        self.current_game
-                            .enemy_alive_list
-                            .get(current_enemy_index)
-                            .unwrap()
-                            .clone(),
-                    );
-                    self.current_game
-                        .enemy_alive_list
-                        .remove(current_enemy_index);
-                } else if current_enemy.draw_and_reach_base_check(ctx, canvas, &self.current_game.map) {
-                    //despawn the ones that reached the base and apply dmg
-                    //TODO: Despawn if reached any buildings as well
-                    self.current_game.building_hash_map.get_mut(&current_enemy.building_hit.unwrap()).unwrap().health -= current_enemy
-                        .health;
-                    self.current_game
-                        .enemy_alive_list
-                        .remove(current_enemy_index);
-                }
-                if current_enemy_index == 0 {
-                    break;
-                }
-                current_enemy_index -= 1;
-            }
-        }
+    fn draw_enemies(&self, ctx: &mut Context, canvas: &mut Canvas) {
         //draw dead enemies
-        for enemy in &mut self.current_game.enemy_dead_list {
+        for enemy in &self.simulation.enemy_dead_list {
             enemy.draw_dead(ctx, canvas);
         }
+        for enemy in &self.simulation.enemy_alive_list {
+            enemy.draw(ctx, canvas);
+        }
     }
     fn draw_ui(&mut self, ctx: &mut Context, canvas: &mut Canvas) {
-        let uniform_og_scale = 30.0 * self.current_game.camera_zoom_ratio;
+        let uniform_og_scale = 30.0 * self.camera_zoom_ratio;
         let uniform_px_scale = PxScale::from(uniform_og_scale);
         let uniform_rescale = 0.1;
         //build health text
         let health_text_format = format!(
             "{} / {}",
-            self.current_game.building_hash_map.get(&0).unwrap().health as i32,
-            self.current_game.building_hash_map.get(&0).unwrap().max_health as i32
+            self.simulation.building_hash_map.get(&0).unwrap().health as i32,
+            self.simulation
+                .building_hash_map
+                .get(&0)
+                .unwrap()
+                .max_health as i32
         );
         let mut health_text_fragment = TextFragment::new(health_text_format);
         health_text_fragment.color = Some(Color::WHITE);
         health_text_fragment.scale = Some(uniform_px_scale);
         let health_text = Text::new(health_text_fragment);
-        //let health_text_offset = self.current_game.offset_to_screen_cord(ctx, &[0.05, 0.03]);
+        //let health_text_offset = self.offset_to_screen_cord(ctx, &[0.05, 0.03]);
         let health_screen_offset = self.offset_to_screen_cord(ctx, &[0.01, 0.01]);
         //build health bar
-        let health_bar_border_size = 1.5 * self.current_game.camera_zoom_ratio;
-        let health_size: [f32; 2] = [
-            30. * self.current_game.camera_zoom_ratio,
-            7. * self.current_game.camera_zoom_ratio,
-        ];
-        let percent_health =
-        self.current_game.building_hash_map.get(&0).unwrap().health / self.current_game.building_hash_map.get(&0).unwrap().max_health;
+        let health_bar_border_size = 1.5 * self.camera_zoom_ratio;
+        let health_size: [f32; 2] = [30. * self.camera_zoom_ratio, 7. * self.camera_zoom_ratio];
+        let percent_health = self.simulation.building_hash_map.get(&0).unwrap().health
+            / self
+                .simulation
+                .building_hash_map
+                .get(&0)
+                .unwrap()
+                .max_health;
         canvas.draw(
             &ggez::graphics::Quad,
             DrawParam::default()
@@ -538,68 +386,109 @@ impl MyGame {
                     health_screen_offset[0] + health_text_offset_x,
                     health_screen_offset[1] + health_text_offset_y,
                 ]),
+        );
+        //build wave text under the health bar
+        let wave_director = &self.simulation.wave_director;
+        let wave_text_format = match wave_director.countdown() {
+            Some(countdown) => format!(
+                "WAVE {}  NEXT IN {}s",
+                wave_director.wave,
+                countdown.ceil() as u32
+            ),
+            None => format!("WAVE {}", wave_director.wave),
+        };
+        let main_gun = &self.simulation.main_gun;
+        let wave_text_format = format!(
+            "{}  AMMO {}/{}  TARGETS {}/{}",
+            wave_text_format,
+            main_gun.ammo(),
+            main_gun.max_ammo,
+            main_gun.target_info_list.len(),
+            main_gun.max_targets
+        );
+        let mut wave_text_fragment = TextFragment::new(wave_text_format);
+        wave_text_fragment.color = Some(Color::BLACK);
+        wave_text_fragment.scale = Some(uniform_px_scale);
+        let wave_text = Text::new(wave_text_fragment);
+        let wave_screen_offset = self.offset_to_screen_cord(ctx, &[0.01, 0.09]);
+        wave_text.draw(
+            canvas,
+            DrawParam::default()
+                .scale([uniform_rescale, uniform_rescale])
+                .dest([wave_screen_offset[0], wave_screen_offset[1]]),
+        );
+        //build placement hint
+        let placement_text_format = match self.placing {
+            Some(building_type) => format!(
+                "PLACING {}  R ROTATE  RIGHT CLICK CANCEL",
+                building_type.name()
+            )
+            .to_uppercase(),
+            None if !self.simulation.rooftop_view => {
+                "1 SENTRY  2 BARRICADE  3 CRAFTING".to_string()
+            }
+            None => "RIGHT CLICK TARGET REMOVE  F FIRE FIRST  BACKSPACE CLEAR".to_string(),
+        };
+        let mut placement_text_fragment = TextFragment::new(placement_text_format);
+        placement_text_fragment.color = Some(Color::BLACK);
+        placement_text_fragment.scale = Some(uniform_px_scale);
+        let placement_text = Text::new(placement_text_fragment);
+        let placement_screen_offset = self.offset_to_screen_cord(ctx, &[0.01, 0.13]);
+        placement_text.draw(
+            canvas,
+            DrawParam::default()
+                .scale([uniform_rescale, uniform_rescale])
+                .dest([placement_screen_offset[0], placement_screen_offset[1]]),
         )
     }
-    fn manage_workers(&mut self, ctx: &mut Context, canvas: &mut Canvas) {
-        if self.current_game.worker_task_list.len() > 0 {
-            let mut current_worker_index: usize = self.current_game.worker_list.len() - 1;
-
-            loop {
-                let current_worker = &mut self.current_game.worker_list[current_worker_index];
-                if current_worker.health < 0. {
-                    self.current_game.worker_list.remove(current_worker_index);
+    /// Lists every worker down the right side of the screen with how to hire more
+    fn draw_roster(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        let uniform_px_scale = PxScale::from(30.0 * self.camera_zoom_ratio);
+        let uniform_rescale = 0.1;
+        let mut roster_lines = vec![
+            format!(
+                "WORKERS {}  JOBS {}  AUTO COLLECT {}",
+                self.simulation.worker_list.len(),
+                self.simulation.job_board.len(),
+                if self.simulation.auto_collect {
+                    "ON"
                 } else {
-                    if current_worker.avalible_for_task {
-                        if self.current_game.worker_task_list.len() != 0 {
-                            current_worker.task = self.current_game.worker_task_list[0].clone();
-                            current_worker.time_since_path_started =
-                                ctx.time.time_since_start().as_secs_f32();
-                            let start_location: (i32, i32) = current_worker.position;
-                            let goal_location: (f32, f32) = self.current_game.worker_task_list[0].goals[0];
-                            current_worker.avalible_for_task = false;
-                            
-                            //TODO: reimplement ASTAR in worker
-                            //Will have to optimize path builder so that a worker will be able to reach the dead bodies against the flow path system
-                            //let path = self.build_path(start_location, goal_location).unwrap().0;
-                            let current_worker =
-                                &mut self.current_game.worker_list[current_worker_index];
-                            //current_worker.path = path;
-                            self.current_game.worker_task_list.remove(0);
-                        }
-                    } else if current_worker.ready_for_new_path {
-                    }
-                    let current_worker = &mut self.current_game.worker_list[current_worker_index];
-                    current_worker.draw(ctx, canvas);
-                }
-                if current_worker_index == 0 {
-                    break;
+                    "OFF"
                 }
-                current_worker_index -= 1;
-            }
+            ),
+            format!(
+                "H HIRE ({} AMMO)  C AUTO COLLECT  X CANCEL",
+                WORKER_HIRE_COST
+            ),
+        ];
+        for worker in &self.simulation.worker_list {
+            roster_lines.push(format!(
+                "#{}  HP {}  {}  ({}, {})",
+                worker.id,
+                worker.health.max(0.) as i32,
+                worker_render::activity_text(worker),
+                worker.position.0,
+                worker.position.1
+            ));
+        }
+        for (line_index, roster_line) in roster_lines.into_iter().enumerate() {
+            let mut roster_text_fragment = TextFragment::new(roster_line);
+            roster_text_fragment.color = Some(Color::BLACK);
+            roster_text_fragment.scale = Some(uniform_px_scale);
+            let roster_text = Text::new(roster_text_fragment);
+            let roster_screen_offset =
+                self.offset_to_screen_cord(ctx, &[0.72, 0.01 + 0.04 * line_index as f32]);
+            roster_text.draw(
+                canvas,
+                DrawParam::default()
+                    .scale([uniform_rescale, uniform_rescale])
+                    .dest([roster_screen_offset[0], roster_screen_offset[1]]),
+            );
         }
     }
-    fn initiate_task(&mut self, mouse_x: f32, mouse_y: f32) {
-        let mouse_y = -mouse_y;
-        if !self.current_game.rooftop_view {
-            for bad_guy in &self.current_game.enemy_dead_list {
-                let current_hitbox = bad_guy.get_hitbox();
-                if mouse_x > current_hitbox.bottom_left.0
-                    && mouse_x < current_hitbox.bottom_left.0 + current_hitbox.width
-                    && mouse_y > current_hitbox.bottom_left.1
-                    && mouse_y < current_hitbox.bottom_left.1 + current_hitbox.height
-                {
-                    // a dead enemy was clicked
-                    let time_to_collect_body: f32 = 0.5;
-                    let time_to_deposit_body: f32 = 0.5;
-                    let home_cord: (f32, f32) = (0., 0.);
-                    let collect_dead_task = worker::Task {
-                        task_times: vec![time_to_collect_body, time_to_deposit_body],
-                        goals: vec![bad_guy.position, home_cord],
-                    };
-                    self.current_game.worker_task_list.push(collect_dead_task);
-                    break;
-                }
-            }
+    fn draw_workers(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        for worker in &self.simulation.worker_list {
+            worker.draw(ctx, canvas);
         }
     }
 
@@ -630,7 +519,7 @@ impl MyGame {
                 ])
                 .dest([
                     0.15 * window_size.width as f32,
-                    0.15 * window_size.height as f32 as f32,
+                    0.15 * window_size.height as f32,
                 ]),
         );
         canvas.draw(
@@ -643,11 +532,38 @@ impl MyGame {
                 ])
                 .dest([
                     0.20 * window_size.width as f32,
-                    0.18 * window_size.height as f32 as f32,
+                    0.18 * window_size.height as f32,
                 ]),
         );
         //Stats
         //TODO: add important stats
+        let loss_text_format = match self.simulation.loss_reason() {
+            Some(LossReason::AllSentriesLost) => "EVERY SENTRY FELL".to_string(),
+            Some(LossReason::BuildingsLost(limit)) => format!("{} BUILDINGS FELL", limit),
+            _ => "THE BASE FELL".to_string(),
+        };
+        let mut loss_text_fragment = TextFragment::new(loss_text_format);
+        loss_text_fragment.color = Some(Color::BLACK);
+        loss_text_fragment.scale = Some(PxScale::from(0.05 * window_size.height as f32));
+        let loss_text = Text::new(loss_text_fragment);
+        loss_text.draw(
+            &mut canvas,
+            DrawParam::default().dest([
+                (window_size.width as f32 - loss_text.measure(&ctx.gfx).unwrap().x) / 2.,
+                0.3 * window_size.height as f32,
+            ]),
+        );
+        let mut seed_text_fragment = TextFragment::new(format!("SEED {}", self.simulation.seed));
+        seed_text_fragment.color = Some(Color::BLACK);
+        seed_text_fragment.scale = Some(PxScale::from(0.05 * window_size.height as f32));
+        let seed_text = Text::new(seed_text_fragment);
+        seed_text.draw(
+            &mut canvas,
+            DrawParam::default().dest([
+                (window_size.width as f32 - seed_text.measure(&ctx.gfx).unwrap().x) / 2.,
+                0.2 * window_size.height as f32,
+            ]),
+        );
 
         //Play again text
         let mut play_text_fragment = TextFragment::new("FIGHT ONCE MORE");
@@ -676,7 +592,7 @@ impl MyGame {
                 DrawParam::default()
                     .color(Color::BLACK)
                     .scale([0.05 * window_size.width as f32, window_size.height as f32])
-                    .dest([current_bar_start * window_size.width as f32, 0. as f32]),
+                    .dest([current_bar_start * window_size.width as f32, 0_f32]),
             );
         }
         //DRAW BARS Vertical
@@ -688,7 +604,7 @@ impl MyGame {
                 DrawParam::default()
                     .color(Color::BLACK)
                     .scale([window_size.width as f32, 0.05 * window_size.height as f32])
-                    .dest([0. as f32, current_bar_start * window_size.height as f32]),
+                    .dest([0_f32, current_bar_start * window_size.height as f32]),
             );
         }
         //DRAW BUTTON BACKING
@@ -723,25 +639,18 @@ impl MyGame {
         canvas.finish(ctx)
     }
     fn draw_playing(&mut self, ctx: &mut Context) -> GameResult {
-        if self.current_game.building_hash_map.get(&0).unwrap().health < 0. {
-            self.state = State::EndMenu;
-        }
-        if !self.current_game.path_built{
-            self.current_game.map.build_flow_path(&self.current_game.building_hash_map);
-            self.current_game.path_built = true;
-        }
         let mut canvas = graphics::Canvas::from_frame(ctx, Color::WHITE);
         let window = ctx.gfx.window();
         let window_size = window.inner_size();
         let aspect_ratio = window_size.width as f32 / window_size.height as f32;
-        let shake = self.generate_shake_offset();
+        let shake = self.simulation.shake_offset;
         canvas.set_screen_coordinates(Rect::new(
-            (self.current_game.camera_x + shake.0) * aspect_ratio,
-            self.current_game.camera_y + shake.1,
-            (DEFAULT_CAM_SIZE * aspect_ratio) * self.current_game.camera_zoom_ratio,
-            DEFAULT_CAM_SIZE * self.current_game.camera_zoom_ratio,
+            (self.camera_x + shake.0) * aspect_ratio,
+            self.camera_y + shake.1,
+            (DEFAULT_CAM_SIZE * aspect_ratio) * self.camera_zoom_ratio,
+            DEFAULT_CAM_SIZE * self.camera_zoom_ratio,
         ));
-        if self.current_game.rooftop_view {
+        if self.simulation.rooftop_view {
             // draw rooftop scene
             // default x: -50 y : -50
             //the floor
@@ -759,15 +668,10 @@ impl MyGame {
                     .scale([70., 50.])
                     .dest([-35., -25.]),
             );
-            self.current_game
+            self.simulation.main_gun.draw_ammo_loader(ctx, &mut canvas);
+            self.simulation
                 .main_gun
-                .draw_ammo_loader(ctx, &mut canvas);
-            self.current_game.main_gun.draw(
-                ctx,
-                &mut canvas,
-                &mut self.current_game.enemy_alive_list,
-                &mut self.current_game.shake_meter,
-            );
+                .draw(ctx, &mut canvas, self.simulation.time_since_start);
         } else {
             // draw ground scene
             //the floor
@@ -791,17 +695,36 @@ impl MyGame {
                     .scale([10., 10.])
                     .dest([-5., -5.]),
             );
+            self.draw_buildings(ctx, &mut canvas);
+            self.draw_sentries(ctx, &mut canvas);
+            self.draw_crafting_stations(&mut canvas);
+        }
+        self.draw_workers(ctx, &mut canvas);
+        self.draw_enemies(ctx, &mut canvas);
+        if self.simulation.rooftop_view {
+            self.simulation.main_gun.draw_targets(ctx, &mut canvas);
         }
-        self.manage_workers(ctx, &mut canvas);
-        self.manage_enemies(ctx, &mut canvas);
         self.draw_ui(ctx, &mut canvas);
+        self.draw_roster(ctx, &mut canvas);
         canvas.finish(ctx)
     }
 }
 
 impl EventHandler for MyGame {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult {
-        // put logic in the drawing function, will change in future need be
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        // run the simulation at a fixed rate no matter how fast frames are drawn
+        while ctx.time.check_update_time(TICKS_PER_SECOND) {
+            if matches!(self.state, State::Playing) {
+                if let Some(replay_player) = &mut self.replay_player {
+                    replay_player.queue_commands(&mut self.simulation);
+                }
+                self.simulation.step(TICK_DURATION);
+                if self.simulation.is_over() {
+                    self.state = State::EndMenu;
+                }
+            }
+        }
+        self.save_replay();
         Ok(())
     }
     fn mouse_button_down_event(
@@ -812,52 +735,185 @@ impl EventHandler for MyGame {
         y: f32,
     ) -> Result<(), GameError> {
         if matches!(self.state, State::Playing) {
+            self.mouse_position = [x, y];
             match _button {
+                MouseButton::Left if self.placing.is_some() => {
+                    if let Some(command) = self.placement_command(ctx) {
+                        if let Command::PlaceBuilding {
+                            building_type,
+                            x,
+                            y,
+                            rotated,
+                        } = command
+                        {
+                            // the simulation refuses it quietly, let the player know why
+                            if let Err(error) =
+                                self.simulation
+                                    .plan_building(building_type, (x, y), rotated)
+                            {
+                                println!("{}", error);
+                            }
+                        }
+                        self.queue_command(command);
+                    }
+                }
+                MouseButton::Right if self.placing.is_some() => self.placing = None,
                 MouseButton::Left => {
                     // creates new Circle and push to vector
                     let [x, y] = self.screen_cord_to_world_cord(ctx, &[x, y]);
-                    self.current_game.main_gun.initiate_fire_sequence(x, y);
+                    // clicking a sentry on the ground cycles what it shoots at, and a crafting
+                    // station what it makes
+                    let clicked_building = self
+                        .simulation
+                        .building_at((x, -y))
+                        .filter(|_| !self.simulation.rooftop_view);
+                    match clicked_building {
+                        Some(building_id)
+                            if self.simulation.sentry_hash_map.contains_key(&building_id) =>
+                        {
+                            self.queue_command(Command::CycleSentryPriority { building_id })
+                        }
+                        Some(building_id)
+                            if self.simulation.crafting_hash_map.contains_key(&building_id) =>
+                        {
+                            self.queue_command(Command::CycleRecipe { building_id })
+                        }
+                        // clicking an enemy from the rooftop locks the gun on to it
+                        None => match self.simulation.enemy_at((x, -y)) {
+                            Some(enemy_id) if self.simulation.rooftop_view => {
+                                self.queue_command(Command::FireAtEnemy { enemy_id })
+                            }
+                            _ => self.queue_command(Command::Fire { x, y }),
+                        },
+                        _ => self.queue_command(Command::Fire { x, y }),
+                    }
+                }
+                MouseButton::Right if self.simulation.rooftop_view => {
+                    // right clicking a target marker takes it back out of the queue
+                    let [x, y] = self.screen_cord_to_world_cord(ctx, &[x, y]);
+                    if let Some(index) = self.simulation.main_gun.target_at(x, y) {
+                        self.queue_command(Command::RemoveTarget { index });
+                    }
                 }
                 MouseButton::Right => {
-                    // creates new Circle and push to vector
                     let [x, y] = self.screen_cord_to_world_cord(ctx, &[x, y]);
-                    self.initiate_task(x, y);
+                    // right clicking a damaged building on the ground sends a worker to repair it
+                    let damaged_building = self
+                        .simulation
+                        .building_at((x, -y))
+                        .filter(|_| !self.simulation.rooftop_view)
+                        .filter(|id| {
+                            let building = &self.simulation.building_hash_map[id];
+                            building.health < building.max_health
+                        });
+                    match damaged_building {
+                        Some(building_id) => {
+                            self.queue_command(Command::RepairBuilding { building_id })
+                        }
+                        None => self.queue_command(Command::CollectBody { x, y }),
+                    }
                 }
                 _ => {}
             }
         } else if matches!(self.state, State::StartMenu) {
-            match _button {
-                MouseButton::Left => {
-                    self.state = State::Playing;
-                }
-                _ => {}
-            }
-        } else if matches!(self.state, State::EndMenu) {
-            match _button {
-                MouseButton::Left => {
-                    self.reset();
-                }
-                _ => {}
+            if _button == MouseButton::Left {
+                self.state = State::Playing;
             }
+        } else if matches!(self.state, State::EndMenu) && _button == MouseButton::Left {
+            self.reset();
         }
         Ok(())
     }
-    fn key_down_event(
+    fn mouse_motion_event(
         &mut self,
         _ctx: &mut Context,
+        x: f32,
+        y: f32,
+        _dx: f32,
+        _dy: f32,
+    ) -> Result<(), GameError> {
+        self.mouse_position = [x, y];
+        Ok(())
+    }
+    fn key_down_event(
+        &mut self,
+        ctx: &mut Context,
         input: ggez::input::keyboard::KeyInput,
         _repeated: bool,
     ) -> Result<(), ggez::GameError> {
         if matches!(self.state, State::Playing) {
             match input.keycode {
-                Some(VirtualKeyCode::Tab) => self.switch_perspective(),
+                Some(VirtualKeyCode::Tab) => {
+                    self.placing = None;
+                    self.queue_command(Command::SwitchPerspective);
+                    Ok(())
+                }
+                // building only happens on the ground
+                Some(VirtualKeyCode::Key1) if !self.simulation.rooftop_view => {
+                    self.placing = Some(BuildingType::Sentry);
+                    Ok(())
+                }
+                Some(VirtualKeyCode::Key2) if !self.simulation.rooftop_view => {
+                    self.placing = Some(BuildingType::Baricade);
+                    Ok(())
+                }
+                Some(VirtualKeyCode::Key3) if !self.simulation.rooftop_view => {
+                    self.placing = Some(BuildingType::CraftingStation);
+                    Ok(())
+                }
+                Some(VirtualKeyCode::R) => {
+                    self.placement_rotated = !self.placement_rotated;
+                    Ok(())
+                }
+                Some(VirtualKeyCode::Escape) => {
+                    self.placing = None;
+                    Ok(())
+                }
                 Some(VirtualKeyCode::Q) => self.change_camera_zoom(false),
                 Some(VirtualKeyCode::E) => self.change_camera_zoom(true),
                 Some(VirtualKeyCode::W) => self.change_camera_location('w'),
                 Some(VirtualKeyCode::A) => self.change_camera_location('a'),
                 Some(VirtualKeyCode::S) => self.change_camera_location('s'),
                 Some(VirtualKeyCode::D) => self.change_camera_location('d'),
-                Some(VirtualKeyCode::Z) => self.spawn_enemy(),
+                Some(VirtualKeyCode::Z) => {
+                    self.queue_command(Command::CallNextWave);
+                    Ok(())
+                }
+                Some(VirtualKeyCode::H) => {
+                    let ammo = self.simulation.main_gun.ammo();
+                    if ammo < WORKER_HIRE_COST {
+                        println!(
+                            "hiring a worker takes {} shells, only {} loaded",
+                            WORKER_HIRE_COST, ammo
+                        );
+                    }
+                    self.queue_command(Command::HireWorker);
+                    Ok(())
+                }
+                Some(VirtualKeyCode::C) => {
+                    self.queue_command(Command::ToggleAutoCollect);
+                    Ok(())
+                }
+                Some(VirtualKeyCode::Back) => {
+                    self.queue_command(Command::ClearTargets);
+                    Ok(())
+                }
+                Some(VirtualKeyCode::F) if self.simulation.rooftop_view => {
+                    // the target under the mouse is fired at next
+                    let [x, y] = self.screen_cord_to_world_cord(ctx, &self.mouse_position);
+                    if let Some(index) = self.simulation.main_gun.target_at(x, y) {
+                        self.queue_command(Command::PrioritizeTarget { index });
+                    }
+                    Ok(())
+                }
+                Some(VirtualKeyCode::X) => {
+                    // cancels the job for the body or building under the mouse
+                    let [x, y] = self.screen_cord_to_world_cord(ctx, &self.mouse_position);
+                    if let Some(task_id) = self.simulation.task_at((x, -y)) {
+                        self.queue_command(Command::CancelTask { task_id });
+                    }
+                    Ok(())
+                }
                 _ => Ok(()),
             }
         } else {
@@ -1,297 +1,239 @@
-use ggez::event::{self, EventHandler, MouseButton};
-use ggez::graphics::{self, Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect};
-use ggez::input::keyboard::{self, KeyInput};
-use ggez::mint::Point2;
-use ggez::winit::event::VirtualKeyCode;
-use ggez::{Context, ContextBuilder, GameError, GameResult};
-use libm::sqrt;
+use std::collections::VecDeque;
+
 use nalgebra::base::Vector2;
 use nalgebra::geometry::Rotation2;
-use nalgebra::{center, Rotation};
-use std::default;
-use std::ops::Add;
-use std::time::Duration;
+use nalgebra::Rotation;
 
+use crate::crafting::ShellKind;
 use crate::enemy;
+use crate::map::Map;
+use crate::spatial::SpatialIndex;
+pub const EXPLOSION_GROW_LENGTH: f32 = 0.5;
+pub const EXPLOSION_BURN_LENGTH: f32 = 4.;
+pub const EXPLOSION_FADE_LENGTH: f32 = 2.;
+pub const EXPLOSION_ANIMATION_LENGTH: f32 =
+    EXPLOSION_GROW_LENGTH + EXPLOSION_BURN_LENGTH + EXPLOSION_FADE_LENGTH;
+/// Rounds of refining the intercept of a locked target, each one aims where the enemy will be
+/// after the time the last aim point took to hit
+const LEAD_ITERATIONS: u32 = 8;
+/// How close to a queued target a click has to be to pick its marker, in world units
+pub const TARGET_MARKER_RADIUS: f32 = 8.;
+/// How a blast's damage drops off from its center to its edge
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DamageFalloff {
+    /// full damage anywhere in the blast
+    #[default]
+    Flat,
+    /// drops evenly towards the edge
+    Linear,
+    /// stays high around the center and drops quickly near the edge
+    Quadratic,
+}
+impl DamageFalloff {
+    /// Part of the damage dealt at a distance from the center, given as a fraction of the radius,
+    /// `edge_damage` of it is still dealt right at the edge
+    pub fn multiplier(&self, distance_ratio: f32, edge_damage: f32) -> f32 {
+        let distance_ratio = distance_ratio.clamp(0., 1.);
+        let drop = match self {
+            DamageFalloff::Flat => 0.,
+            DamageFalloff::Linear => distance_ratio,
+            DamageFalloff::Quadratic => distance_ratio * distance_ratio,
+        };
+        1. - (1. - edge_damage) * drop
+    }
+}
 #[derive(Default)]
-struct TargetInfo {
-    x: f32,
-    y: f32,
-    rotation: Rotation<f32, 2>,
-    rotation_started: bool,
+pub struct TargetInfo {
+    pub x: f32,
+    pub y: f32,
+    pub rotation: Rotation<f32, 2>,
+    pub rotation_started: bool,
+    /// enemy the target is locked on, the point is moved every step to where the shell meets it
+    pub enemy_id: Option<u32>,
+}
+/// A fired shell on its way from the turret at the origin to where it was aimed
+pub struct ShellInFlight {
+    /// landing point, y up like `TargetInfo`
+    pub x: f32,
+    pub y: f32,
+    pub shell: ShellKind,
+    pub fired_time: f32,
+    /// seconds from the muzzle to the ground
+    pub flight_time: f32,
 }
-struct ExplosionInfo {
-    x: f32,
-    y: f32,
-    started_time: f32,
-    added_to_shake_meter: bool,
+impl ShellInFlight {
+    /// How far along its flight the shell is, from 0 at the muzzle to 1 on landing
+    pub fn progress(&self, current_time: f32) -> f32 {
+        if self.flight_time <= 0. {
+            return 1.;
+        }
+        ((current_time - self.fired_time) / self.flight_time).clamp(0., 1.)
+    }
+}
+pub struct ExplosionInfo {
+    pub x: f32,
+    pub y: f32,
+    pub started_time: f32,
+    pub added_to_shake_meter: bool,
+    pub shell: ShellKind,
+    pub radius: f32,
+    /// time burning has been applied up to, for incendiary shells
+    pub burned_until: f32,
 }
 #[derive(Default)]
 pub struct MainGun {
-    pub(crate) enabled: bool,
-    pub(crate) shell_explosive_radius: f32,
-    pub(crate) damage: f32,
-    pub(crate) explosion_info_list: Vec<ExplosionInfo>,
-    pub(crate) fired_count: u32,
-    pub(crate) last_fired: f32,
-    pub(crate) since_fired: f32,
-    pub(crate) shooting_duration: f32,
-    pub(crate) current_rotation: f32,
-    pub(crate) target_info_list: Vec<TargetInfo>,
-    pub(crate) rotation_speed_per_second: f32,
-    pub(crate) last_rotation: f32,
+    pub enabled: bool,
+    pub shell_explosive_radius: f32,
+    pub damage: f32,
+    pub explosion_info_list: Vec<ExplosionInfo>,
+    pub fired_count: u32,
+    pub last_fired: f32,
+    pub since_fired: f32,
+    pub shooting_duration: f32,
+    pub current_rotation: f32,
+    pub target_info_list: Vec<TargetInfo>,
+    pub rotation_speed_per_second: f32,
+    pub last_rotation: f32,
+    /// shells ready to fire, the front one is fired next
+    pub magazine: VecDeque<ShellKind>,
+    /// most shells the loader holds
+    pub max_ammo: u32,
+    /// rounds crafted from each point of corpse value
+    pub rounds_per_corpse_value: f32,
+    /// part of a round left over from earlier corpses
+    pub ammo_progress: f32,
+    /// world units per second a shell travels at once fired
+    pub muzzle_velocity: f32,
+    pub shells_in_flight: Vec<ShellInFlight>,
+    pub damage_falloff: DamageFalloff,
+    /// part of the damage still dealt at the very edge of a blast
+    pub edge_damage: f32,
+    /// most targets that can wait in the queue, further clicks are ignored
+    pub max_targets: usize,
 }
 impl MainGun {
-    fn get_barrel_segment_positions(&self) -> Vec<Rect> {
-        let initial_animation_length = 0.15 * self.shooting_duration;
-        let return_animation_length = 0.85 * self.shooting_duration;
-
-        let base_radius = 9.5; //minused .5 for overlap
-
-        let longest_barrel_height = 15.;
-        let longest_barrel_dif = 9.;
-
-        let middle_barrel_height = 6.;
-        let middle_barrel_dif = 2.;
-
-        let smallest_barrel_height = 3.;
-        let smallest_barrel_dif = 1.;
-
-        let mut barrels: Vec<Rect> = Vec::new();
-        if self.since_fired < self.shooting_duration && self.fired_count > 0 {
-            if self.since_fired < initial_animation_length {
-                //first pulling back shot annimation
-                let percentage_through_animation = self.since_fired / initial_animation_length;
-                barrels.push(Rect {
-                    x: -2.,
-                    y: 0.0
-                        - base_radius
-                        - smallest_barrel_height
-                        - middle_barrel_height
-                        - longest_barrel_height
-                        + (percentage_through_animation
-                            * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
-                    h: 2.,
-                    w: 4.,
-                });
-                barrels.push(Rect {
-                    x: -1.5,
-                    y: 0.0
-                        - base_radius
-                        - smallest_barrel_height
-                        - middle_barrel_height
-                        - longest_barrel_height
-                        + (percentage_through_animation
-                            * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
-                    h: longest_barrel_height - (percentage_through_animation * longest_barrel_dif),
-                    w: 3.,
-                });
-                barrels.push(Rect {
-                    x: -2.5,
-                    y: 0.0 - base_radius - smallest_barrel_height - middle_barrel_height
-                        + (percentage_through_animation
-                            * (middle_barrel_dif + smallest_barrel_dif)),
-                    h: middle_barrel_height - (percentage_through_animation * middle_barrel_dif),
-                    w: 5.,
-                });
-                barrels.push(Rect {
-                    x: -3.,
-                    y: 0.0 - base_radius - smallest_barrel_height
-                        + (percentage_through_animation * smallest_barrel_dif),
-                    h: smallest_barrel_height
-                        - (percentage_through_animation * smallest_barrel_dif),
-                    w: 6.,
-                });
-            } else {
-                //returning from shot animation
-                let percentage_through_animation =
-                    1. - ((self.since_fired - initial_animation_length) / return_animation_length);
-                barrels.push(Rect {
-                    x: -2.,
-                    y: 0.0
-                        - base_radius
-                        - smallest_barrel_height
-                        - middle_barrel_height
-                        - longest_barrel_height
-                        + (percentage_through_animation
-                            * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
-                    h: 2.,
-                    w: 4.,
-                });
-                barrels.push(Rect {
-                    x: -1.5,
-                    y: 0.0
-                        - base_radius
-                        - smallest_barrel_height
-                        - middle_barrel_height
-                        - longest_barrel_height
-                        + (percentage_through_animation
-                            * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
-                    h: longest_barrel_height - (percentage_through_animation * longest_barrel_dif),
-                    w: 3.,
-                });
-                barrels.push(Rect {
-                    x: -2.5,
-                    y: 0.0 - base_radius - smallest_barrel_height - middle_barrel_height
-                        + (percentage_through_animation
-                            * (middle_barrel_dif + smallest_barrel_dif)),
-                    h: middle_barrel_height - (percentage_through_animation * middle_barrel_dif),
-                    w: 5.,
-                });
-                barrels.push(Rect {
-                    x: -3.,
-                    y: 0.0 - base_radius - smallest_barrel_height
-                        + (percentage_through_animation * smallest_barrel_dif),
-                    h: smallest_barrel_height
-                        - (percentage_through_animation * smallest_barrel_dif),
-                    w: 6.,
-                });
-            }
-        } else {
-            //println!("Gun is in idle");
-            barrels.push(Rect {
-                x: -2.,
-                y: 0.0
-                    - base_radius
-                    - smallest_barrel_height
-                    - middle_barrel_height
-                    - longest_barrel_height,
-                h: 2.,
-                w: 4.,
-            });
-            barrels.push(Rect {
-                x: -1.5,
-                y: 0.0
-                    - base_radius
-                    - smallest_barrel_height
-                    - middle_barrel_height
-                    - longest_barrel_height,
-                h: longest_barrel_height,
-                w: 3.,
-            });
-            barrels.push(Rect {
-                x: -2.5,
-                y: 0.0 - base_radius - smallest_barrel_height - middle_barrel_height,
-                h: middle_barrel_height,
-                w: 5.,
-            });
-            barrels.push(Rect {
-                x: -3.,
-                y: 0.0 - base_radius - smallest_barrel_height,
-                h: smallest_barrel_height,
-                w: 6.,
-            });
+    /// Shells left in the loader
+    pub fn ammo(&self) -> u32 {
+        self.magazine.len() as u32
+    }
+    /// Puts a shell at the back of the loader, false when it is already full
+    pub fn load_shell(&mut self, shell: ShellKind) -> bool {
+        if self.ammo() >= self.max_ammo {
+            return false;
         }
-        return barrels;
+        self.magazine.push_back(shell);
+        true
     }
-    fn draw_explosions(&mut self, canvas: &mut Canvas, ctx: &mut Context, shake_meter: &mut u8) {
-        if self.explosion_info_list.len() != 0 {
-            if self.explosion_info_list.len() != 0 {
-                let current_time = ctx.time.time_since_start().as_secs_f32();
-                let mut current_explosion_index = self.explosion_info_list.len() - 1;
-                let first_animation_length = 0.5;
-                let second_animation_length = 4.;
-                let third_animation_length = 2.;
-                let mut deleted = false;
-                let mut explosion = MeshBuilder::new();
-                loop {
-                    let cur_explosion = &mut self.explosion_info_list[current_explosion_index];
-                    let time_since_explosion = current_time - cur_explosion.started_time;
-                    if time_since_explosion < first_animation_length {
-                        let percentage_through = time_since_explosion / first_animation_length;
-                        //create and grow yellow circle
-                        explosion
-                            .circle(
-                                graphics::DrawMode::fill(),
-                                [cur_explosion.x, -cur_explosion.y],
-                                self.shell_explosive_radius * percentage_through,
-                                0.1,
-                                Color::YELLOW,
-                            )
-                            .unwrap();
-                    } else if time_since_explosion
-                        < first_animation_length + second_animation_length
-                    {
-                        if !cur_explosion.added_to_shake_meter {
-                            if shake_meter.add(20) < 100 as u8 {
-                                *shake_meter = shake_meter.add(20);
-                            } else if *shake_meter < 100 as u8 {
-                                *shake_meter = 100 as u8;
-                            }
-                            cur_explosion.added_to_shake_meter = true;
-                        }
-                        let percentage_through = (time_since_explosion - first_animation_length)
-                            / second_animation_length;
-                        //create red and shrink yellow
-                        explosion
-                            .circle(
-                                graphics::DrawMode::fill(),
-                                [cur_explosion.x, -cur_explosion.y],
-                                self.shell_explosive_radius,
-                                0.1,
-                                Color::from_rgb(
-                                    220 - (115. * percentage_through) as u8,
-                                    20 + (85. * percentage_through) as u8,
-                                    60 + (45. * percentage_through) as u8,
-                                ),
-                            )
-                            .unwrap()
-                            .circle(
-                                graphics::DrawMode::fill(),
-                                [cur_explosion.x, -cur_explosion.y],
-                                self.shell_explosive_radius * (1. - percentage_through),
-                                0.1,
-                                Color::YELLOW,
-                            )
-                            .unwrap();
-                    } else if time_since_explosion
-                        < (first_animation_length
-                            + second_animation_length
-                            + third_animation_length)
-                    {
-                        let percentage_through = (time_since_explosion
-                            - first_animation_length
-                            - second_animation_length)
-                            / third_animation_length;
-                        //grow grey circle over while still shrinking
-                        explosion
-                            .circle(
-                                graphics::DrawMode::fill(),
-                                [cur_explosion.x, -cur_explosion.y],
-                                self.shell_explosive_radius,
-                                0.1,
-                                Color::from_rgba(
-                                    220 - (115. * percentage_through) as u8,
-                                    20 + (85. * percentage_through) as u8,
-                                    60 + (45. * percentage_through) as u8,
-                                    (255. * (1. - percentage_through)) as u8,
-                                ),
-                            )
-                            .unwrap();
-                    } else {
-                        self.explosion_info_list.remove(current_explosion_index);
-                        deleted = true;
-                    }
-                    if !deleted {
-                        let mesh_data = explosion.build();
-                        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
-                        canvas.draw(&mesh, DrawParam::default());
-                    }
-                    if current_explosion_index == 0 {
-                        break;
+    /// Turns a deposited corpse into standard rounds, keeping leftover value towards the next one
+    pub fn load_corpse(&mut self, corpse_value: u32) {
+        self.ammo_progress += corpse_value as f32 * self.rounds_per_corpse_value;
+        let rounds = self.ammo_progress.floor();
+        self.ammo_progress -= rounds;
+        for _ in 0..rounds as u32 {
+            self.load_shell(ShellKind::Standard);
+        }
+    }
+    /// Retires finished explosions, burns enemies inside incendiary ones and feeds the shake
+    /// meter once an explosion reaches full size
+    fn update_explosions(
+        &mut self,
+        current_time: f32,
+        enemy_alive_list: &mut [enemy::Enemy],
+        enemy_index: &SpatialIndex,
+        shake_meter: &mut u8,
+    ) {
+        self.explosion_info_list
+            .retain(|explosion| current_time - explosion.started_time < EXPLOSION_ANIMATION_LENGTH);
+        for explosion in &mut self.explosion_info_list {
+            let time_since_explosion = current_time - explosion.started_time;
+            if time_since_explosion >= EXPLOSION_GROW_LENGTH && !explosion.added_to_shake_meter {
+                *shake_meter = shake_meter.saturating_add(20).min(100);
+                explosion.added_to_shake_meter = true;
+            }
+            // burning only happens while the blast is at full size
+            let burn_start = explosion.started_time + EXPLOSION_GROW_LENGTH;
+            let burn_end = burn_start + EXPLOSION_BURN_LENGTH;
+            let burned_from = explosion.burned_until.max(burn_start);
+            let burned_to = current_time.min(burn_end);
+            if explosion.shell.burn_damage() > 0. && burned_to > burned_from {
+                let burn_damage = explosion.shell.burn_damage() * (burned_to - burned_from);
+                for enemy_index in MainGun::blast_candidates(
+                    (explosion.x, explosion.y),
+                    explosion.radius,
+                    enemy_index,
+                ) {
+                    let enemy = &mut enemy_alive_list[enemy_index];
+                    if MainGun::in_blast((explosion.x, explosion.y), explosion.radius, enemy) {
+                        enemy.health -= burn_damage;
                     }
-                    current_explosion_index -= 1;
                 }
+                explosion.burned_until = burned_to;
             }
         }
     }
-    pub fn move_and_check_fire(
+    /// Seconds until a shell aimed at a world point now would land: the rest of the reload, the
+    /// turn to face the point and the flight there
+    pub fn time_to_hit(&self, current_time: f32, x: f32, y: f32) -> f32 {
+        let reload_left = if self.fired_count == 0 {
+            0.
+        } else {
+            (self.shooting_duration - (current_time - self.last_fired)).max(0.)
+        };
+        let aim = Rotation2::rotation_between(&Vector2::new(0., 1.), &Vector2::new(x, y));
+        let turn = Rotation2::new(-self.current_rotation)
+            .rotation_to(&aim)
+            .angle()
+            .abs();
+        let turn_time = if self.rotation_speed_per_second > 0. {
+            turn / self.rotation_speed_per_second
+        } else {
+            0.
+        };
+        let flight_time = if self.muzzle_velocity > 0. {
+            (x * x + y * y).sqrt() / self.muzzle_velocity
+        } else {
+            0.
+        };
+        reload_left + turn_time + flight_time
+    }
+    /// Moves every locked target to where its enemy will be when a shell sent now lands, targets
+    /// whose enemy has died are dropped
+    pub fn lead_targets(
         &mut self,
-        time_since_start: Duration,
-        enemy_alive_list: &mut Vec<enemy::Enemy>,
+        current_time: f32,
+        enemy_alive_list: &[enemy::Enemy],
+        map: &Map,
     ) {
-        let time_since_start_sec = time_since_start.as_secs_f32();
-        if self.target_info_list.len() == 0 || self.since_fired < self.shooting_duration {
+        self.target_info_list.retain(|target| {
+            target
+                .enemy_id
+                .is_none_or(|enemy_id| enemy_alive_list.iter().any(|enemy| enemy.id == enemy_id))
+        });
+        for target_index in 0..self.target_info_list.len() {
+            let Some(enemy_id) = self.target_info_list[target_index].enemy_id else {
+                continue;
+            };
+            let enemy = enemy_alive_list
+                .iter()
+                .find(|enemy| enemy.id == enemy_id)
+                .unwrap();
+            //world space is y up
+            let (velocity_x, velocity_y) = enemy.velocity(map);
+            let (mut x, mut y) = (enemy.position.0, -enemy.position.1);
+            for _ in 0..LEAD_ITERATIONS {
+                let time_to_hit = self.time_to_hit(current_time, x, y);
+                x = enemy.position.0 + velocity_x * time_to_hit;
+                y = -enemy.position.1 - velocity_y * time_to_hit;
+            }
+            let target = &mut self.target_info_list[target_index];
+            target.x = x;
+            target.y = y;
+            // the gun turns towards the new point on its next move
+            target.rotation_started = false;
+        }
+    }
+    pub fn move_and_check_fire(&mut self, time_since_start_sec: f32) {
+        if self.target_info_list.is_empty() || self.since_fired < self.shooting_duration {
             self.last_rotation = time_since_start_sec;
             return;
         }
@@ -321,261 +263,203 @@ impl MainGun {
 
         // choose a way to turn and do it
         if needed_rotation < 0.0 {
-            if needed_rotation * -1. < movement_ammount {
+            if -needed_rotation < movement_ammount {
                 self.current_rotation = -self.target_info_list[0].rotation.angle();
-                self.fire(time_since_start, enemy_alive_list);
+                self.fire(time_since_start_sec);
             } else {
                 self.current_rotation += movement_ammount;
             }
+        } else if needed_rotation < movement_ammount {
+            self.current_rotation = -self.target_info_list[0].rotation.angle();
+            self.fire(time_since_start_sec);
         } else {
-            if needed_rotation < movement_ammount {
-                self.current_rotation = -self.target_info_list[0].rotation.angle();
-                self.fire(time_since_start, enemy_alive_list);
-            } else {
-                self.current_rotation -= movement_ammount;
-            }
+            self.current_rotation -= movement_ammount;
         }
         self.last_rotation = time_since_start_sec;
     }
-    pub fn draw(
+    /// Advances the gun by one simulation step: turning, firing and explosion lifetimes
+    ///
+    /// `enemy_index` has to be up to date with `enemy_alive_list`
+    pub fn update(
         &mut self,
-        ctx: &mut Context,
-        canvas: &mut Canvas,
-        enemy_alive_list: &mut Vec<enemy::Enemy>,
+        current_time: f32,
+        enemy_alive_list: &mut [enemy::Enemy],
+        enemy_index: &SpatialIndex,
         shake_meter: &mut u8,
     ) {
-        self.draw_explosions(canvas, ctx, shake_meter);
-        let time_since_start_as_sec = ctx.time.time_since_start().as_secs_f32();
-        self.since_fired = time_since_start_as_sec - self.last_fired;
-        let mut mesh_builder = MeshBuilder::new();
-        let barrel_positions = self.get_barrel_segment_positions();
-        let mut triangle_opacity = 0;
-        if self.since_fired < 0.05 {
-            triangle_opacity = 255
-        }
-        mesh_builder
-            .circle(
-                graphics::DrawMode::fill(),
-                [0., 0.],
-                10.,
-                0.0001,
-                Color::from_rgb(60, 60, 60),
-            )
-            .unwrap()
-            .rectangle(
-                graphics::DrawMode::fill(),
-                barrel_positions[0],
-                Color::from_rgb(60, 60, 60),
-            )
-            .unwrap()
-            .rectangle(
-                graphics::DrawMode::fill(),
-                barrel_positions[1],
-                Color::from_rgb(60, 60, 60),
-            )
-            .unwrap()
-            .rectangle(
-                graphics::DrawMode::fill(),
-                barrel_positions[2],
-                Color::from_rgb(60, 60, 60),
-            )
-            .unwrap()
-            .rectangle(
-                graphics::DrawMode::fill(),
-                barrel_positions[3],
-                Color::from_rgb(60, 60, 60),
-            )
-            .unwrap()
-            .polygon(
-                graphics::DrawMode::fill(),
-                &[[-2., -32.5], [-2., -30.5], [-8., -31.5]],
-                Color::from_rgba(255, 255, 51, triangle_opacity),
-            )
-            .unwrap()
-            .polygon(
-                graphics::DrawMode::fill(),
-                &[[2., -32.5], [2., -30.5], [8., -31.5]],
-                Color::from_rgba(255, 255, 51, triangle_opacity),
-            )
-            .unwrap();
-        // MAIN GOAL: figure out the positions of each of the barrel segments and draw them
-        let mesh_data = mesh_builder.build();
-        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
-        self.move_and_check_fire(ctx.time.time_since_start(), enemy_alive_list);
-        canvas.draw(&mesh, DrawParam::default().rotation(self.current_rotation))
+        self.land_shells(current_time, enemy_alive_list, enemy_index);
+        self.update_explosions(current_time, enemy_alive_list, enemy_index, shake_meter);
+        self.since_fired = current_time - self.last_fired;
+        self.move_and_check_fire(current_time);
     }
-    fn draw_artillary_round(
-        &self,
-        left_x: f32,
-        bottom_y: f32,
-        scale: f32,
-        ctx: &mut Context,
-        canvas: &mut Canvas,
+    /// Explodes every shell that has reached the ground
+    fn land_shells(
+        &mut self,
+        current_time: f32,
+        enemy_alive_list: &mut [enemy::Enemy],
+        enemy_index: &SpatialIndex,
     ) {
-        //builds an artillary round based on the bottom left corner a size ratio
-        let bottom_y = -bottom_y;
-        let width = 1. * scale;
-        let mut artillary_round = MeshBuilder::new();
-        let height_of_casing = -5. * scale;
-        let height_of_neck = -3. * scale;
-        let height_of_tip = -2. * scale;
-        let neck_loss = 0.2 * scale;
-        artillary_round
-            .rectangle(
-                DrawMode::fill(),
-                Rect {
-                    x: left_x,
-                    y: bottom_y,
-                    w: width,
-                    h: height_of_casing,
-                },
-                Color::from_rgb(69, 75, 27),
-            )
-            .unwrap()
-            .polygon(
-                DrawMode::fill(),
-                &[
-                    [left_x, bottom_y + height_of_casing],
-                    [
-                        left_x + neck_loss,
-                        bottom_y + height_of_casing + height_of_neck,
-                    ],
-                    [
-                        left_x + (width - neck_loss),
-                        bottom_y + height_of_casing + height_of_neck,
-                    ],
-                    [left_x + width, bottom_y + height_of_casing],
-                ],
-                Color::from_rgb(69, 75, 27),
-            )
-            .unwrap()
-            .polygon(
-                DrawMode::fill(),
-                &[
-                    [
-                        left_x + neck_loss,
-                        bottom_y + height_of_casing + height_of_neck,
-                    ],
-                    [
-                        left_x + (width / 2.),
-                        bottom_y + height_of_casing + height_of_neck + height_of_tip,
-                    ],
-                    [
-                        left_x + (width - neck_loss),
-                        bottom_y + height_of_casing + height_of_neck,
-                    ],
-                ],
-                Color::YELLOW,
-            )
-            .unwrap();
-        let mesh_data = artillary_round.build();
-        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
-        canvas.draw(&mesh, DrawParam::default())
-    }
-    pub fn draw_ammo_loader(&self, ctx: &mut Context, canvas: &mut Canvas) {
-        //let mut ammo_loader = MeshBuilder::new();
-        let mut percentage_through = 0.;
-        canvas.draw(
-            &ggez::graphics::Quad,
-            DrawParam::default()
-                .color(Color::from_rgb(128, 128, 128))
-                .scale([24., 11.5])
-                .dest([0., -5.5]),
-        );
-        if self.shooting_duration > self.since_fired {
-            percentage_through = self.since_fired / self.shooting_duration;
-            //this is the 8th shot comming up
-            self.draw_artillary_round(
-                22. + (0.75 + (0.25 * percentage_through)),
-                -2.5 - (2.5 * percentage_through),
-                0.5 + (percentage_through / 2.),
-                ctx,
-                canvas,
-            );
-        }
-        let mut counter = 0.;
-        while counter < 16. {
-            if counter % 2. != 1. {
-                counter += 1.
-            }
-            self.draw_artillary_round(
-                8. + counter - (percentage_through * 2.),
-                -5.,
-                1.,
-                ctx,
-                canvas,
-            );
-            counter += 1.;
+        let (landed, in_flight): (Vec<ShellInFlight>, Vec<ShellInFlight>) =
+            std::mem::take(&mut self.shells_in_flight)
+                .into_iter()
+                .partition(|shell| shell.progress(current_time) >= 1.);
+        self.shells_in_flight = in_flight;
+        for shell in landed {
+            self.explode(&shell, current_time, enemy_alive_list, enemy_index);
         }
-        canvas.draw(
-            &ggez::graphics::Quad,
-            DrawParam::default()
-                .color(Color::BLACK)
-                .scale([3., 12.])
-                .dest([23.5, -6.]),
-        );
     }
     pub fn initiate_fire_sequence(&mut self, x: f32, y: f32) {
         //feeding a target to the maingun
-        if self.enabled {
+        if self.enabled && !self.target_queue_full() {
             self.target_info_list.push(TargetInfo {
-                x: x,
-                y: y,
+                x,
+                y,
                 rotation: Rotation::default(),
                 rotation_started: false,
+                enemy_id: None,
             });
         }
-        println!("added new target at x:{} y:{}", x, y);
     }
-    pub fn fire(&mut self, time_since_start: Duration, enemy_alive_list: &mut Vec<enemy::Enemy>) {
+    /// Queues a target that follows an enemy and leads it, see `lead_targets`
+    pub fn lock_on(&mut self, enemy: &enemy::Enemy) {
+        if self.enabled && !self.target_queue_full() {
+            self.target_info_list.push(TargetInfo {
+                x: enemy.position.0,
+                y: -enemy.position.1,
+                rotation: Rotation::default(),
+                rotation_started: false,
+                enemy_id: Some(enemy.id),
+            });
+        }
+    }
+    pub fn target_queue_full(&self) -> bool {
+        self.target_info_list.len() >= self.max_targets
+    }
+    /// Index of the queued target whose marker is closest to a world position, None when no
+    /// marker is within `TARGET_MARKER_RADIUS`
+    pub fn target_at(&self, x: f32, y: f32) -> Option<usize> {
+        let distance_squared =
+            |target: &TargetInfo| (target.x - x).powi(2) + (target.y - y).powi(2);
+        self.target_info_list
+            .iter()
+            .enumerate()
+            .filter(|(_, target)| {
+                distance_squared(target) <= TARGET_MARKER_RADIUS * TARGET_MARKER_RADIUS
+            })
+            .min_by(|(_, a), (_, b)| distance_squared(a).total_cmp(&distance_squared(b)))
+            .map(|(index, _)| index)
+    }
+    /// Takes a target out of the queue before it is fired at
+    pub fn remove_target(&mut self, index: usize) -> Option<TargetInfo> {
+        (index < self.target_info_list.len()).then(|| self.target_info_list.remove(index))
+    }
+    /// Moves a queued target to the front so it is fired at next
+    pub fn move_target_to_front(&mut self, index: usize) {
+        if index < self.target_info_list.len() {
+            let target = self.target_info_list.remove(index);
+            self.target_info_list.insert(0, target);
+        }
+    }
+    /// Blast radius of the shell that will be fired at the queued target at an index, shells
+    /// are fired in loader order and a standard one is assumed for targets past the loader
+    pub fn predicted_blast_radius(&self, index: usize) -> f32 {
+        let shell = self.magazine.get(index).copied().unwrap_or_default();
+        self.shell_explosive_radius * shell.radius_multiplier()
+    }
+    /// Sends the loaded shell towards the first target, it does its damage once it lands
+    pub fn fire(&mut self, current_time: f32) {
+        if self.magazine.is_empty() {
+            //the target stays queued until a round is loaded
+            return;
+        }
         if (self.since_fired > self.shooting_duration || self.fired_count == 0) && self.enabled {
-            let current_time = time_since_start.as_secs_f32();
-            println!("Gun Has Fired");
-            self.fired_count = self.fired_count + 1;
+            let shell = self.magazine.pop_front().unwrap();
+            self.fired_count += 1;
             self.last_fired = current_time;
-            //build the explosion
-            let center_of_explosion = self.target_info_list.get(0).unwrap();
-            self.explosion_info_list.push(ExplosionInfo {
-                x: center_of_explosion.x,
-                y: center_of_explosion.y,
-                started_time: current_time,
-                added_to_shake_meter: false,
+            let target = self.target_info_list.remove(0);
+            let distance = (target.x * target.x + target.y * target.y).sqrt();
+            // without a muzzle velocity the shell lands straight away
+            let flight_time = if self.muzzle_velocity > 0. {
+                distance / self.muzzle_velocity
+            } else {
+                0.
+            };
+            self.shells_in_flight.push(ShellInFlight {
+                x: target.x,
+                y: target.y,
+                shell,
+                fired_time: current_time,
+                flight_time,
             });
-            //collision check
-            //first check if a box would have hit
-            let hitbox_top_left = (
-                center_of_explosion.x - self.shell_explosive_radius,
-                center_of_explosion.y + self.shell_explosive_radius,
-            );
-            let hitbox_size = self.shell_explosive_radius * 2.;
-            for enemy in enemy_alive_list {
-                //correct y to be negative
-                let enemy_x = enemy.position.0 as f32;
-                let enemy_y = -enemy.position.1 as f32;
-                // check if its in the box version of the explosiown(less expensive to check?)
-                println!(
-                    "check if enemy at ({},{}) is in corner ({},{}), with size: {})",
-                    enemy_x, enemy_y, hitbox_top_left.0, hitbox_top_left.1, hitbox_size
-                );
-                if enemy_x > hitbox_top_left.0
-                    && enemy_x < hitbox_top_left.0 + hitbox_size
-                    && enemy_y < hitbox_top_left.1
-                    && enemy_y < hitbox_top_left.1 + hitbox_size
-                {
-                    println!("Enemy in  Box HITBOX");
-                    // check the circle hitbox wit distance formula from the center of the circle
-                    let dif_x = enemy_x - center_of_explosion.x;
-                    let dif_y = enemy_y - center_of_explosion.y;
-                    let distance_from_center: f32 =
-                        sqrt(dif_x.powf(2.) as f64 + dif_y.powf(2.) as f64) as f32;
-                    if distance_from_center < self.shell_explosive_radius {
-                        //the target was in the circle
-                        enemy.health -= self.damage;
-                        println!("Enemy was hit");
-                    }
-                }
-            }
-            self.target_info_list.remove(0);
+        }
+    }
+    /// Indices of the enemies whose body might reach into a blast centered on a world point
+    fn blast_candidates(center: (f32, f32), radius: f32, enemy_index: &SpatialIndex) -> Vec<usize> {
+        enemy_index.in_radius(
+            (center.0, -center.1),
+            radius + enemy_index.largest_body_radius,
+        )
+    }
+    /// Whether any of an enemy's triangle is inside a blast centered on a world point
+    pub fn in_blast(center: (f32, f32), radius: f32, enemy: &enemy::Enemy) -> bool {
+        let reach = radius + enemy.body_radius();
+        //correct y to be negative
+        let dif_x = enemy.position.0 - center.0;
+        let dif_y = -enemy.position.1 - center.1;
+        // the box around the blast is cheaper to check than the triangle
+        if dif_x.abs() >= reach || dif_y.abs() >= reach {
+            return false;
+        }
+        enemy.distance_to_body((center.0, -center.1)) < radius
+    }
+    /// Damage a blast centered on a world point deals to an enemy, falling off with the distance to
+    /// the nearest part of the enemy's triangle, 0 outside the blast
+    pub fn blast_damage(
+        &self,
+        center: (f32, f32),
+        radius: f32,
+        damage: f32,
+        enemy: &enemy::Enemy,
+    ) -> f32 {
+        if !MainGun::in_blast(center, radius, enemy) {
+            return 0.;
+        }
+        let distance_to_body = enemy.distance_to_body((center.0, -center.1));
+        let distance_ratio = if radius > 0. {
+            distance_to_body / radius
+        } else {
+            0.
+        };
+        damage
+            * self
+                .damage_falloff
+                .multiplier(distance_ratio, self.edge_damage)
+    }
+    /// Starts the explosion of a landed shell and damages the enemies caught in it
+    fn explode(
+        &mut self,
+        center_of_explosion: &ShellInFlight,
+        current_time: f32,
+        enemy_alive_list: &mut [enemy::Enemy],
+        enemy_index: &SpatialIndex,
+    ) {
+        let shell = center_of_explosion.shell;
+        let shell_explosive_radius = self.shell_explosive_radius * shell.radius_multiplier();
+        let damage = self.damage * shell.damage_multiplier();
+        //build the explosion
+        self.explosion_info_list.push(ExplosionInfo {
+            x: center_of_explosion.x,
+            y: center_of_explosion.y,
+            started_time: current_time,
+            added_to_shake_meter: false,
+            shell,
+            radius: shell_explosive_radius,
+            burned_until: current_time,
+        });
+        let center = (center_of_explosion.x, center_of_explosion.y);
+        for enemy_index in MainGun::blast_candidates(center, shell_explosive_radius, enemy_index) {
+            let enemy = &mut enemy_alive_list[enemy_index];
+            enemy.health -= self.blast_damage(center, shell_explosive_radius, damage, enemy);
         }
     }
 }
@@ -0,0 +1,532 @@
+use num::cast::AsPrimitive;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fmt;
+/// Offset from a space to the neighbour flowing into it, indexed like `Direction`
+const DIRECTIONS: [(i32, i32); 8] = [
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (-1, 1),
+    (1, 1),
+    (-1, -1),
+    (1, -1),
+];
+pub const GRID_SIZE: usize = 251;
+/// distance of a grid space no building can be reached from
+pub const UNREACHED: u32 = u32::MAX;
+/// extra cost of walking through a barricade, enemies only break through when going around costs more
+pub const BARRICADE_COST: u32 = 200;
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BuildingType {
+    Sentry,
+    Baricade,
+    /// turns deposited bodies into main gun shells
+    CraftingStation,
+}
+impl BuildingType {
+    /// Grid spaces a newly placed building of this type covers as (width, height)
+    pub fn footprint(&self) -> (usize, usize) {
+        match self {
+            BuildingType::Sentry => (4, 4),
+            BuildingType::Baricade => (6, 2),
+            BuildingType::CraftingStation => (6, 6),
+        }
+    }
+    /// Lowercase name used in replays and the HUD
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildingType::Sentry => "sentry",
+            BuildingType::Baricade => "barricade",
+            BuildingType::CraftingStation => "crafting",
+        }
+    }
+    pub fn max_health(&self) -> f32 {
+        match self {
+            BuildingType::Sentry => 300.,
+            BuildingType::Baricade => 500.,
+            BuildingType::CraftingStation => 400.,
+        }
+    }
+}
+/// Why a building can not be placed where it was asked to go
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlacementError {
+    OutOfBounds,
+    Overlap,
+    /// enemies would have no way left from the edge of the map to the main building
+    SealsBase,
+}
+impl fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            PlacementError::OutOfBounds => "it is outside the map",
+            PlacementError::Overlap => "it overlaps another building",
+            PlacementError::SealsBase => "it would seal the base off",
+        };
+        write!(f, "building can not be placed, {}", reason)
+    }
+}
+#[derive(Clone)]
+pub enum Direction {
+    Top,
+    Left,
+    Right,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+impl Direction {
+    pub fn new(index: usize) -> Self {
+        match index {
+            0 => Direction::Top,
+            1 => Direction::Left,
+            2 => Direction::Right,
+            3 => Direction::Bottom,
+            4 => Direction::TopLeft,
+            5 => Direction::TopRight,
+            6 => Direction::BottomLeft,
+            7 => Direction::BottomRight,
+            _ => panic!("Used an index outside of possible Directions"),
+        }
+    }
+    pub fn is_diagonal(&self) -> bool {
+        self.clone() as usize >= 4
+    }
+    /// Unit vector an enemy on this space walks along, in screen space
+    pub fn heading(&self) -> (f32, f32) {
+        let offset = DIRECTIONS[self.clone() as usize];
+        let length = if self.is_diagonal() {
+            std::f32::consts::SQRT_2
+        } else {
+            1.
+        };
+        (-offset.0 as f32 / length, -offset.1 as f32 / length)
+    }
+}
+/// Ground type of a grid space, sets how costly it is to path through and how fast enemies cross it
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Terrain {
+    Ground,
+    /// only laid through `Map::set_terrain` for now, nothing places it on a new map
+    Mud,
+    /// left behind by destroyed buildings
+    Rubble,
+}
+impl Terrain {
+    /// Cost of entering a space of this terrain, ground is 10 so slower terrain can be a fraction more
+    pub fn cost(&self) -> u32 {
+        match self {
+            Terrain::Ground => 10,
+            Terrain::Mud => 30,
+            Terrain::Rubble => 20,
+        }
+    }
+    /// Speed multiplier for enemies walking on it
+    pub fn speed_ratio(&self) -> f32 {
+        Terrain::Ground.cost() as f32 / self.cost() as f32
+    }
+}
+#[derive(Clone)]
+pub struct BuildingGridInfo {
+    pub id: u32,
+    pub typ: BuildingType,
+}
+///Will either be used to direct enemies or assign damage if an enemy attacks it
+#[derive(Clone)]
+pub struct GridSpace {
+    pub building: Option<BuildingGridInfo>,
+    pub direction: Option<Direction>,
+    pub terrain: Terrain,
+    /// extra cost from sentry kill zones covering the space
+    pub threat: u32,
+    /// summed cost to the cheapest building along the flow path, 0 on buildings enemies attack
+    pub distance: u32,
+}
+impl GridSpace {
+    /// Buildings the flow path leads to, barricades are only in the way
+    pub fn is_goal(&self) -> bool {
+        matches!(&self.building, Some(building) if !matches!(building.typ, BuildingType::Baricade))
+    }
+    /// Cost of stepping onto this space
+    pub fn cost(&self) -> u32 {
+        let barricade_cost = match &self.building {
+            Some(building) if matches!(building.typ, BuildingType::Baricade) => BARRICADE_COST,
+            _ => 0,
+        };
+        self.terrain.cost() + self.threat + barricade_cost
+    }
+}
+#[derive(Clone)]
+pub struct Map {
+    pub map: Vec<Vec<GridSpace>>,
+}
+///Grid system for placing objects and pathing enemies
+impl Map {
+    pub fn new(building_hash_map: &HashMap<u32, Building>) -> Self {
+        let empty_grid_space: GridSpace = GridSpace {
+            building: None,
+            direction: None,
+            terrain: Terrain::Ground,
+            threat: 0,
+            distance: UNREACHED,
+        };
+        let mut default_map = Map {
+            map: vec![vec![empty_grid_space; GRID_SIZE]; GRID_SIZE],
+        };
+        //add any existing buildings before initial map build
+        for (current_building_id, building) in building_hash_map {
+            default_map.stamp_building(building, Some(*current_building_id));
+        }
+        default_map.build_flow_path();
+        default_map
+    }
+    /// Columns and rows a building covers, bottom left is in screen space so it spans upwards
+    fn footprint(building: &Building) -> (std::ops::Range<usize>, std::ops::Range<usize>) {
+        (
+            building.bottom_left.0..building.bottom_left.0 + building.width,
+            building.bottom_left.1 - building.height..building.bottom_left.1,
+        )
+    }
+    /// Writes the building id under its footprint, or clears it when there is none, without touching the flow path
+    fn stamp_building(&mut self, building: &Building, building_id: Option<u32>) {
+        let (columns, rows) = Map::footprint(building);
+        for column in &mut self.map[columns] {
+            for grid_space in &mut column[rows.clone()] {
+                grid_space.building = building_id.map(|id| BuildingGridInfo {
+                    id,
+                    typ: building.building_type,
+                });
+            }
+        }
+    }
+    /// Every grid space in a footprint plus the ring around it, diagonal steps past its corners change with it
+    fn footprint_spaces(building: &Building) -> Vec<(usize, usize)> {
+        let (columns, rows) = Map::footprint(building);
+        let columns = columns.start.saturating_sub(1)..(columns.end + 1).min(GRID_SIZE);
+        let rows = rows.start.saturating_sub(1)..(rows.end + 1).min(GRID_SIZE);
+        columns
+            .flat_map(|x| rows.clone().map(move |y| (x, y)))
+            .collect()
+    }
+    /// Checks a building fits on the grid without overlapping or walling the main building in
+    pub fn check_placement(&self, building: &Building) -> Result<(), PlacementError> {
+        if building.width == 0
+            || building.height == 0
+            || building.bottom_left.0 + building.width > GRID_SIZE
+            || building.bottom_left.1 > GRID_SIZE
+            || building.bottom_left.1 < building.height
+        {
+            return Err(PlacementError::OutOfBounds);
+        }
+        let (columns, rows) = Map::footprint(building);
+        let in_footprint = |(x, y): (usize, usize)| columns.contains(&x) && rows.contains(&y);
+        if columns
+            .clone()
+            .any(|x| rows.clone().any(|y| self.map[x][y].building.is_some()))
+        {
+            return Err(PlacementError::Overlap);
+        }
+        // walk in from every open edge space and see if the main building can still be reached
+        let mut visited = vec![vec![false; GRID_SIZE]; GRID_SIZE];
+        let mut walk_queue: VecDeque<(usize, usize)> = VecDeque::new();
+        for edge in 0..GRID_SIZE {
+            for location in [
+                (edge, 0),
+                (edge, GRID_SIZE - 1),
+                (0, edge),
+                (GRID_SIZE - 1, edge),
+            ] {
+                if self.map[location.0][location.1].building.is_none()
+                    && !in_footprint(location)
+                    && !visited[location.0][location.1]
+                {
+                    visited[location.0][location.1] = true;
+                    walk_queue.push_back(location);
+                }
+            }
+        }
+        while let Some(current_location) = walk_queue.pop_front() {
+            // only straight steps, enemies can not cut between two buildings touching at a corner
+            for (index, neighbour) in Map::neighbours(current_location) {
+                if Direction::new(index).is_diagonal() || visited[neighbour.0][neighbour.1] {
+                    continue;
+                }
+                match &self.map[neighbour.0][neighbour.1].building {
+                    Some(building_grid_info) if building_grid_info.id == 0 => return Ok(()),
+                    None if !in_footprint(neighbour) => {
+                        visited[neighbour.0][neighbour.1] = true;
+                        walk_queue.push_back(neighbour);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Err(PlacementError::SealsBase)
+    }
+    /// Places a building and re-floods only the spaces it changes, see `check_placement` before calling
+    pub fn add_building(&mut self, building_id: u32, building: &Building) {
+        self.stamp_building(building, Some(building_id));
+        self.repair(Map::footprint_spaces(building));
+    }
+    /// Clears a building and re-floods the spaces that were flowing into or through it
+    pub fn remove_building(&mut self, building: &Building) {
+        self.stamp_building(building, None);
+        self.repair(Map::footprint_spaces(building));
+    }
+    /// Grid spaces within a radius of a grid position, clipped to the grid
+    fn spaces_in_radius(center: (usize, usize), radius: usize) -> Vec<(usize, usize)> {
+        let columns = center.0.saturating_sub(radius)..(center.0 + radius + 1).min(GRID_SIZE);
+        let rows = center.1.saturating_sub(radius)..(center.1 + radius + 1).min(GRID_SIZE);
+        columns
+            .flat_map(|x| rows.clone().map(move |y| (x, y)))
+            .filter(|(x, y)| {
+                center.0.abs_diff(*x).pow(2) + center.1.abs_diff(*y).pow(2) <= radius.pow(2)
+            })
+            .collect()
+    }
+    /// Makes the spaces around a sentry more costly so enemies path around it when they can
+    pub fn add_kill_zone(&mut self, center: (usize, usize), radius: usize, threat: u32) {
+        let spaces = Map::spaces_in_radius(center, radius);
+        for (x, y) in &spaces {
+            self.map[*x][*y].threat += threat;
+        }
+        self.repair(spaces);
+    }
+    /// Takes back a kill zone added with the same center, radius and threat
+    pub fn remove_kill_zone(&mut self, center: (usize, usize), radius: usize, threat: u32) {
+        let spaces = Map::spaces_in_radius(center, radius);
+        for (x, y) in &spaces {
+            let grid_space = &mut self.map[*x][*y];
+            grid_space.threat = grid_space.threat.saturating_sub(threat);
+        }
+        self.repair(spaces);
+    }
+    /// Lays terrain over a rectangle of grid spaces, bottom left in screen space like buildings
+    pub fn set_terrain(
+        &mut self,
+        bottom_left: (usize, usize),
+        width: usize,
+        height: usize,
+        terrain: Terrain,
+    ) {
+        let spaces: Vec<(usize, usize)> = (bottom_left.0..(bottom_left.0 + width).min(GRID_SIZE))
+            .flat_map(|x| {
+                (bottom_left.1.saturating_sub(height)..bottom_left.1.min(GRID_SIZE))
+                    .map(move |y| (x, y))
+            })
+            .collect();
+        for (x, y) in &spaces {
+            self.map[*x][*y].terrain = terrain;
+        }
+        self.repair(spaces);
+    }
+    /// Moves a building's footprint to its new size, the flow path follows along
+    pub fn resize_building(&mut self, building_id: u32, old: &Building, new: &Building) {
+        self.remove_building(old);
+        self.add_building(building_id, new);
+    }
+    ///used to convert -500.0 to 500.0 to the grid system of 0 to 250 lossfully
+    ///
+    /// Positions off the map land on the nearest edge space
+    pub fn convert_position_to_grid_position(position: (f32, f32)) -> (usize, usize) {
+        let new_position: (f32, f32) = (((position.0 + 500.) / 4.), ((position.1 + 500.) / 4.));
+        let grid_position: (usize, usize) = (new_position.0.as_(), new_position.1.as_());
+        (
+            grid_position.0.min(GRID_SIZE - 1),
+            grid_position.1.min(GRID_SIZE - 1),
+        )
+    }
+    /// Grid spaces next to a location that are still on the grid, with the direction index used to reach them
+    fn neighbours(location: (usize, usize)) -> impl Iterator<Item = (usize, (usize, usize))> {
+        DIRECTIONS
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, direction)| {
+                let x = location.0 as i32 + direction.0;
+                let y = location.1 as i32 + direction.1;
+                if x < 0 || y < 0 || x >= GRID_SIZE as i32 || y >= GRID_SIZE as i32 {
+                    //direction is off the grid
+                    return None;
+                }
+                Some((index, (x as usize, y as usize)))
+            })
+    }
+    //Path system built into the grid system that priorizies nearest objective
+    /// Rebuilds the whole flow path from scratch, buildings only need to be stamped on the grid
+    pub fn build_flow_path(&mut self) {
+        // feed the queue every building space so the flow spreads out from their walls
+        let mut spread_queue = BinaryHeap::new();
+        for (x, column) in self.map.iter_mut().enumerate() {
+            for (y, grid_space) in column.iter_mut().enumerate() {
+                grid_space.direction = None;
+                if grid_space.is_goal() {
+                    grid_space.distance = 0;
+                    spread_queue.push(Reverse((0, x, y)));
+                } else {
+                    grid_space.distance = UNREACHED;
+                }
+            }
+        }
+        self.spread(spread_queue);
+    }
+    /// Re-floods the flow path after the cost or building of some spaces changed
+    ///
+    /// The changed spaces and everything that flowed through them lose their direction,
+    /// then the flow spreads back in from the untouched spaces around them
+    fn repair(&mut self, changed: Vec<(usize, usize)>) {
+        let mut spread_queue = BinaryHeap::new();
+        let mut invalidated: Vec<(usize, usize)> = Vec::new();
+        let mut invalidate_stack: Vec<(usize, usize)> = Vec::new();
+        for (x, y) in changed {
+            let grid_space = &mut self.map[x][y];
+            grid_space.direction = None;
+            if grid_space.is_goal() {
+                grid_space.distance = 0;
+                spread_queue.push(Reverse((0, x, y)));
+            } else {
+                grid_space.distance = UNREACHED;
+                invalidate_stack.push((x, y));
+            }
+        }
+        while let Some(current_location) = invalidate_stack.pop() {
+            invalidated.push(current_location);
+            for (index, neighbour) in Map::neighbours(current_location) {
+                let grid_space = &mut self.map[neighbour.0][neighbour.1];
+                // the neighbour flows into this space when it points back along the same direction
+                let flows_here = matches!(&grid_space.direction, Some(direction) if direction.clone() as usize == index);
+                if flows_here {
+                    grid_space.direction = None;
+                    grid_space.distance = UNREACHED;
+                    invalidate_stack.push(neighbour);
+                }
+            }
+        }
+        // spread back in from whatever still has a path around the cleared region
+        for current_location in invalidated {
+            for (_, neighbour) in Map::neighbours(current_location) {
+                let distance = self.map[neighbour.0][neighbour.1].distance;
+                if distance != UNREACHED {
+                    spread_queue.push(Reverse((distance, neighbour.0, neighbour.1)));
+                }
+            }
+        }
+        self.spread(spread_queue);
+    }
+    /// Dijkstra flood of the integration field outwards from the queued spaces
+    ///
+    /// A space's direction points at the neighbour its cheapest route goes through, the queue
+    /// is ordered by distance then position so the same map always floods the same way
+    fn spread(&mut self, mut spread_queue: BinaryHeap<Reverse<(u32, usize, usize)>>) {
+        // now MAKE IT SPREAD
+        while let Some(Reverse((distance, x, y))) = spread_queue.pop() {
+            // a cheaper path reached this space after it was queued
+            if distance > self.map[x][y].distance {
+                continue;
+            }
+            // give all surrounding gridspaces that are reached cheaper from here directions to the current space
+            for (index, neighbour) in Map::neighbours((x, y)) {
+                let diagonal = Direction::new(index).is_diagonal();
+                // no cutting across the corner of a building
+                if diagonal
+                    && (self.map[neighbour.0][y].building.is_some()
+                        || self.map[x][neighbour.1].building.is_some())
+                {
+                    continue;
+                }
+                let neighbour_space: &mut GridSpace = &mut self.map[neighbour.0][neighbour.1];
+                if neighbour_space.is_goal() {
+                    continue;
+                }
+                let mut step_cost = neighbour_space.cost();
+                if diagonal {
+                    // roughly the square root of two
+                    step_cost = step_cost * 14 / 10;
+                }
+                let neighbour_distance = distance + step_cost;
+                if neighbour_distance < neighbour_space.distance {
+                    neighbour_space.distance = neighbour_distance;
+                    neighbour_space.direction = Some(Direction::new(index));
+                    spread_queue.push(Reverse((neighbour_distance, neighbour.0, neighbour.1)));
+                }
+            }
+        }
+    }
+    /// Spaces a worker can step to from a location with the cost of the step
+    ///
+    /// Buildings block workers except `home_building`, the one they are walking out of,
+    /// and like the flow path diagonals can not cut across a building's corner
+    pub fn find_moveable_options(
+        &self,
+        location: (usize, usize),
+        home_building: Option<u32>,
+    ) -> Vec<((usize, usize), u32)> {
+        let blocked = |x: usize, y: usize| match &self.map[x][y].building {
+            Some(building) => Some(building.id) != home_building,
+            None => false,
+        };
+        Map::neighbours(location)
+            .filter(|(index, neighbour)| {
+                !blocked(neighbour.0, neighbour.1)
+                    && !(Direction::new(*index).is_diagonal()
+                        && (blocked(neighbour.0, location.1) || blocked(location.0, neighbour.1)))
+            })
+            .map(|(index, neighbour)| {
+                // workers ignore sentry threat, only the ground slows them down
+                let mut step_cost = self.map[neighbour.0][neighbour.1].terrain.cost();
+                if Direction::new(index).is_diagonal() {
+                    step_cost = step_cost * 14 / 10;
+                }
+                (neighbour, step_cost)
+            })
+            .collect()
+    }
+}
+//TODO: ADD more details to add variance for other buildings besides barriers
+#[derive(Clone)]
+pub struct Building {
+    pub building_type: BuildingType,
+    pub bottom_left: (usize, usize),
+    pub width: usize,
+    pub height: usize,
+    pub max_health: f32,
+    pub health: f32,
+}
+impl Building {
+    /// Center of the building in screen space
+    pub fn center(&self) -> (f32, f32) {
+        (
+            (self.bottom_left.0 as f32 + self.width as f32 / 2.) * 4. - 500.,
+            (self.bottom_left.1 as f32 - self.height as f32 / 2.) * 4. - 500.,
+        )
+    }
+}
+/// What is left of a destroyed building, slows down anything walking over it
+#[derive(Clone, Debug)]
+pub struct Rubble {
+    pub building_type: BuildingType,
+    pub bottom_left: (usize, usize),
+    pub width: usize,
+    pub height: usize,
+}
+///position used for defining a place for pathing
+pub struct Pos {
+    pub bottom_left: (i32, i32),
+    pub scale: (i32, i32),
+    pub center: (i32, i32),
+}
+impl Pos {
+    pub fn new(bottom_left: (i32, i32), scale: (i32, i32)) -> Self {
+        Pos {
+            bottom_left,
+            scale,
+            center: (bottom_left.0 + (scale.0 / 2), bottom_left.1 + (scale.1 / 2)),
+        }
+    }
+    pub fn distance_to_center(&self, given_point: (i32, i32)) -> u32 {
+        self.center.0.abs_diff(given_point.0) + self.center.1.abs_diff(given_point.1)
+    }
+}
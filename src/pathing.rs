@@ -1,25 +1,81 @@
 use pathfinding::prelude::astar;
+use std::collections::HashSet;
 
-pub fn build_path_star(
-    &self,
-    start_location: (i32, i32),
-    goal_bottom_left: (i32, i32),
-    goal_scale: (i32, i32)
-) -> Option<(Vec<(i32, i32)>, u32)> {
-    let goal_limit = (goal_bottom_left.0 + goal_scale.0, goal_bottom_left.1 + goal_scale.1);
-    let distance_x = 
-    let result: Option<(Vec<(i32, i32)>, u32)> = astar(
+use crate::map::{Map, GRID_SIZE};
+
+/// Center of a grid space in screen space like `Enemy::position`, y pointing down, the inverse of
+/// `Map::convert_position_to_grid_position`
+pub fn grid_to_screen_position(grid_position: (usize, usize)) -> (i32, i32) {
+    (
+        grid_position.0 as i32 * 4 - 500 + 2,
+        grid_position.1 as i32 * 4 - 500 + 2,
+    )
+}
+/// Grid space under a screen space position, positions off the map snap to its edge
+fn screen_to_grid_position(position: (f32, f32)) -> (usize, usize) {
+    let clamped = (position.0.clamp(-500., 499.), position.1.clamp(-500., 499.));
+    let grid_position = Map::convert_position_to_grid_position(clamped);
+    (
+        grid_position.0.min(GRID_SIZE - 1),
+        grid_position.1.min(GRID_SIZE - 1),
+    )
+}
+/// The free grid space closest to a location, itself when no building stands on it
+///
+/// Searches outwards ring by ring through building spaces, so a target inside a footprint
+/// ends up at the nearest space next to its walls, straight out from it where possible
+fn nearest_free_space(map: &Map, location: (usize, usize)) -> Option<(usize, usize)> {
+    let mut visited = HashSet::from([location]);
+    let mut ring = vec![location];
+    while !ring.is_empty() {
+        let closest_free = ring
+            .iter()
+            .filter(|(x, y)| map.map[*x][*y].building.is_none())
+            .min_by_key(|(x, y)| {
+                let dif_x = x.abs_diff(location.0);
+                let dif_y = y.abs_diff(location.1);
+                (dif_x * dif_x + dif_y * dif_y, *x, *y)
+            });
+        if let Some(closest_free) = closest_free {
+            return Some(*closest_free);
+        }
+        let mut next_ring = Vec::new();
+        for (current_x, current_y) in ring {
+            for x in current_x.saturating_sub(1)..=(current_x + 1).min(GRID_SIZE - 1) {
+                for y in current_y.saturating_sub(1)..=(current_y + 1).min(GRID_SIZE - 1) {
+                    if visited.insert((x, y)) {
+                        next_ring.push((x, y));
+                    }
+                }
+            }
+        }
+        ring = next_ring;
+    }
+    None
+}
+/// Cheapest walk between two screen space positions for a worker, going around buildings
+///
+/// A worker standing inside a building can walk out of it, a goal inside a building's
+/// footprint is swapped for the nearest free space next to it. The path is the centers of
+/// every grid space along the way in screen space, starting with the one the worker
+/// stands on, or None when the goal can not be reached
+pub fn build_path(map: &Map, start: (f32, f32), goal: (f32, f32)) -> Option<Vec<(i32, i32)>> {
+    let start_location = screen_to_grid_position(start);
+    let goal_location = nearest_free_space(map, screen_to_grid_position(goal))?;
+    let home_building = map.map[start_location.0][start_location.1]
+        .building
+        .as_ref()
+        .map(|building| building.id);
+    let (path, _cost) = astar(
         &start_location,
+        |&location| map.find_moveable_options(location, home_building),
         |&(x, y)| {
-            self.current_game
-                .map
-                .clone()
-                .find_moveable_options(x, y)
-                .into_iter()
-                .map(|p| (p, 1))
+            // octile distance over plain ground, never more than the real cost
+            let dif_x = x.abs_diff(goal_location.0) as u32;
+            let dif_y = y.abs_diff(goal_location.1) as u32;
+            10 * dif_x.max(dif_y) + 4 * dif_x.min(dif_y)
         },
-        |&(x, y)| (goal.0.abs_diff(x) + goal.1.abs_diff(y)) / 3,
-        |&(x,y)| ,
-    );
-    result
-}
\ No newline at end of file
+        |&location| location == goal_location,
+    )?;
+    Some(path.into_iter().map(grid_to_screen_position).collect())
+}
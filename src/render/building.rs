@@ -0,0 +1,72 @@
+use ggez::graphics::{Canvas, Color, DrawParam, Quad};
+use the_last_hope::map::{Building, BuildingType, Rubble};
+
+/// World units covered by one grid space
+const GRID_SPACE_SIZE: f32 = 4.;
+pub trait BuildingRender {
+    fn draw(&self, canvas: &mut Canvas);
+}
+/// Draws a grid footprint, bottom left in screen space grid spaces like `Building`
+fn draw_footprint(
+    canvas: &mut Canvas,
+    bottom_left: (i32, i32),
+    width: usize,
+    height: usize,
+    color: Color,
+) {
+    canvas.draw(
+        &Quad,
+        DrawParam::default()
+            .color(color)
+            .scale([
+                width as f32 * GRID_SPACE_SIZE,
+                height as f32 * GRID_SPACE_SIZE,
+            ])
+            .dest([
+                bottom_left.0 as f32 * GRID_SPACE_SIZE - 500.,
+                (bottom_left.1 - height as i32) as f32 * GRID_SPACE_SIZE - 500.,
+            ]),
+    );
+}
+impl BuildingRender for Building {
+    fn draw(&self, canvas: &mut Canvas) {
+        let mut color = match self.building_type {
+            BuildingType::Sentry => Color::from_rgb(60, 60, 70),
+            BuildingType::Baricade => Color::from_rgb(120, 85, 50),
+            BuildingType::CraftingStation => Color::from_rgb(70, 90, 60),
+        };
+        // fades towards black as it takes damage
+        let health_ratio = (self.health / self.max_health).clamp(0., 1.);
+        color.r *= 0.4 + 0.6 * health_ratio;
+        color.g *= 0.4 + 0.6 * health_ratio;
+        color.b *= 0.4 + 0.6 * health_ratio;
+        let bottom_left = (self.bottom_left.0 as i32, self.bottom_left.1 as i32);
+        draw_footprint(canvas, bottom_left, self.width, self.height, color);
+    }
+}
+impl BuildingRender for Rubble {
+    fn draw(&self, canvas: &mut Canvas) {
+        let bottom_left = (self.bottom_left.0 as i32, self.bottom_left.1 as i32);
+        draw_footprint(
+            canvas,
+            bottom_left,
+            self.width,
+            self.height,
+            Color::from_rgb(95, 88, 80),
+        );
+    }
+}
+/// Translucent preview of a building being placed, green where it fits and red where it does not
+pub fn draw_ghost(
+    canvas: &mut Canvas,
+    bottom_left: (i32, i32),
+    (width, height): (usize, usize),
+    valid: bool,
+) {
+    let color = if valid {
+        Color::new(0., 0.8, 0., 0.5)
+    } else {
+        Color::new(0.9, 0., 0., 0.5)
+    };
+    draw_footprint(canvas, bottom_left, width, height, color);
+}
@@ -0,0 +1,53 @@
+use ggez::graphics::{self, Canvas, Color, DrawParam, PxScale, Quad, Text, TextFragment};
+use the_last_hope::crafting::{CraftingStation, ShellKind};
+
+pub trait CraftingStationRender {
+    fn draw(&self, canvas: &mut Canvas);
+}
+impl CraftingStationRender for CraftingStation {
+    fn draw(&self, canvas: &mut Canvas) {
+        //recipe letter and how many bodies are waiting
+        let recipe_letter = match self.recipe {
+            ShellKind::Standard => "S",
+            ShellKind::HighExplosive => "H",
+            ShellKind::Incendiary => "I",
+        };
+        let mut recipe_text_fragment =
+            TextFragment::new(format!("{} {}", recipe_letter, self.input_queue.len()));
+        recipe_text_fragment.color = Some(Color::WHITE);
+        recipe_text_fragment.scale = Some(PxScale::from(40.));
+        let recipe_text = Text::new(recipe_text_fragment);
+        graphics::Drawable::draw(
+            &recipe_text,
+            canvas,
+            DrawParam::default()
+                .scale([0.1, 0.1])
+                .dest([self.position.0 - 8., self.position.1 - 8.]),
+        );
+        //progress on the shell being made, full and yellow while it waits for room in the loader
+        let progress = match (self.crafting, self.finished) {
+            (Some((shell, progress)), _) => progress / shell.recipe().processing_time,
+            (None, Some(_)) => 1.,
+            (None, None) => return,
+        };
+        let bar_color = if self.finished.is_some() {
+            Color::YELLOW
+        } else {
+            Color::GREEN
+        };
+        canvas.draw(
+            &Quad,
+            DrawParam::default()
+                .color(Color::BLACK)
+                .scale([16., 2.])
+                .dest([self.position.0 - 8., self.position.1 + 4.]),
+        );
+        canvas.draw(
+            &Quad,
+            DrawParam::default()
+                .color(bar_color)
+                .scale([16. * progress.min(1.), 2.])
+                .dest([self.position.0 - 8., self.position.1 + 4.]),
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use ggez::graphics::{self, Canvas, Color, DrawParam, Mesh, MeshBuilder};
+use ggez::mint::Point2;
+use ggez::Context;
+use the_last_hope::enemy::{Enemy, STROKE_WIDTH};
+
+const ORGIN: (f32, f32) = (0.0, 0.0);
+pub trait EnemyRender {
+    fn draw_dead(&self, ctx: &mut Context, canvas: &mut Canvas);
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas);
+}
+fn build_triangle(
+    enemy: &Enemy,
+    ctx: &Context,
+    draw_mode: graphics::DrawMode,
+    color: Color,
+) -> Mesh {
+    let mut mesh_builder = MeshBuilder::new();
+    let half_size = enemy.size * enemy.width;
+    mesh_builder
+        .polygon(
+            draw_mode,
+            &[
+                [ORGIN.0, ORGIN.1 + enemy.size],
+                [ORGIN.0 - half_size, ORGIN.1 - enemy.size],
+                [ORGIN.0 + half_size, ORGIN.1 - enemy.size],
+            ],
+            color,
+        )
+        .unwrap();
+    let mesh_data = mesh_builder.build();
+    Mesh::from_data(&ctx.gfx, mesh_data)
+}
+impl EnemyRender for Enemy {
+    fn draw_dead(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        let mesh = build_triangle(
+            self,
+            ctx,
+            graphics::DrawMode::stroke(STROKE_WIDTH),
+            // corpses keep their colour, just darker
+            Color::from_rgb(self.color[0] / 2, self.color[1] / 2, self.color[2] / 2),
+        );
+        let current_position_point = Point2 {
+            x: self.position.0,
+            y: self.position.1,
+        };
+        canvas.draw(
+            &mesh,
+            DrawParam::default()
+                .rotation(self.rotation)
+                .dest(current_position_point),
+        );
+    }
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        // build the triangle around the current point
+        let color = Color::from_rgb(self.color[0], self.color[1], self.color[2]);
+        let mesh = build_triangle(self, ctx, graphics::DrawMode::fill(), color);
+        let current_position_point = Point2 {
+            x: self.position.0,
+            y: self.position.1,
+        };
+        canvas.draw(
+            &mesh,
+            DrawParam::default()
+                .rotation(self.rotation)
+                .dest(current_position_point),
+        );
+    }
+}
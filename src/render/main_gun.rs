@@ -0,0 +1,492 @@
+use ggez::graphics::{
+    self, Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, PxScale, Rect, Text, TextFragment,
+};
+use ggez::Context;
+use the_last_hope::crafting::ShellKind;
+use the_last_hope::main_gun::{
+    MainGun, EXPLOSION_ANIMATION_LENGTH, EXPLOSION_BURN_LENGTH, EXPLOSION_FADE_LENGTH,
+    EXPLOSION_GROW_LENGTH, TARGET_MARKER_RADIUS,
+};
+
+pub trait MainGunRender {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, current_time: f32);
+    fn draw_ammo_loader(&self, ctx: &mut Context, canvas: &mut Canvas);
+    fn draw_targets(&self, ctx: &mut Context, canvas: &mut Canvas);
+}
+fn get_barrel_segment_positions(main_gun: &MainGun) -> Vec<Rect> {
+    let initial_animation_length = 0.15 * main_gun.shooting_duration;
+    let return_animation_length = 0.85 * main_gun.shooting_duration;
+
+    let base_radius = 9.5; //minused .5 for overlap
+
+    let longest_barrel_height = 15.;
+    let longest_barrel_dif = 9.;
+
+    let middle_barrel_height = 6.;
+    let middle_barrel_dif = 2.;
+
+    let smallest_barrel_height = 3.;
+    let smallest_barrel_dif = 1.;
+
+    let mut barrels: Vec<Rect> = Vec::new();
+    if main_gun.since_fired < main_gun.shooting_duration && main_gun.fired_count > 0 {
+        if main_gun.since_fired < initial_animation_length {
+            //first pulling back shot annimation
+            let percentage_through_animation = main_gun.since_fired / initial_animation_length;
+            barrels.push(Rect {
+                x: -2.,
+                y: 0.0
+                    - base_radius
+                    - smallest_barrel_height
+                    - middle_barrel_height
+                    - longest_barrel_height
+                    + (percentage_through_animation
+                        * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
+                h: 2.,
+                w: 4.,
+            });
+            barrels.push(Rect {
+                x: -1.5,
+                y: 0.0
+                    - base_radius
+                    - smallest_barrel_height
+                    - middle_barrel_height
+                    - longest_barrel_height
+                    + (percentage_through_animation
+                        * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
+                h: longest_barrel_height - (percentage_through_animation * longest_barrel_dif),
+                w: 3.,
+            });
+            barrels.push(Rect {
+                x: -2.5,
+                y: 0.0 - base_radius - smallest_barrel_height - middle_barrel_height
+                    + (percentage_through_animation * (middle_barrel_dif + smallest_barrel_dif)),
+                h: middle_barrel_height - (percentage_through_animation * middle_barrel_dif),
+                w: 5.,
+            });
+            barrels.push(Rect {
+                x: -3.,
+                y: 0.0 - base_radius - smallest_barrel_height
+                    + (percentage_through_animation * smallest_barrel_dif),
+                h: smallest_barrel_height - (percentage_through_animation * smallest_barrel_dif),
+                w: 6.,
+            });
+        } else {
+            //returning from shot animation
+            let percentage_through_animation =
+                1. - ((main_gun.since_fired - initial_animation_length) / return_animation_length);
+            barrels.push(Rect {
+                x: -2.,
+                y: 0.0
+                    - base_radius
+                    - smallest_barrel_height
+                    - middle_barrel_height
+                    - longest_barrel_height
+                    + (percentage_through_animation
+                        * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
+                h: 2.,
+                w: 4.,
+            });
+            barrels.push(Rect {
+                x: -1.5,
+                y: 0.0
+                    - base_radius
+                    - smallest_barrel_height
+                    - middle_barrel_height
+                    - longest_barrel_height
+                    + (percentage_through_animation
+                        * (longest_barrel_dif + middle_barrel_dif + smallest_barrel_dif)),
+                h: longest_barrel_height - (percentage_through_animation * longest_barrel_dif),
+                w: 3.,
+            });
+            barrels.push(Rect {
+                x: -2.5,
+                y: 0.0 - base_radius - smallest_barrel_height - middle_barrel_height
+                    + (percentage_through_animation * (middle_barrel_dif + smallest_barrel_dif)),
+                h: middle_barrel_height - (percentage_through_animation * middle_barrel_dif),
+                w: 5.,
+            });
+            barrels.push(Rect {
+                x: -3.,
+                y: 0.0 - base_radius - smallest_barrel_height
+                    + (percentage_through_animation * smallest_barrel_dif),
+                h: smallest_barrel_height - (percentage_through_animation * smallest_barrel_dif),
+                w: 6.,
+            });
+        }
+    } else {
+        //println!("Gun is in idle");
+        barrels.push(Rect {
+            x: -2.,
+            y: 0.0
+                - base_radius
+                - smallest_barrel_height
+                - middle_barrel_height
+                - longest_barrel_height,
+            h: 2.,
+            w: 4.,
+        });
+        barrels.push(Rect {
+            x: -1.5,
+            y: 0.0
+                - base_radius
+                - smallest_barrel_height
+                - middle_barrel_height
+                - longest_barrel_height,
+            h: longest_barrel_height,
+            w: 3.,
+        });
+        barrels.push(Rect {
+            x: -2.5,
+            y: 0.0 - base_radius - smallest_barrel_height - middle_barrel_height,
+            h: middle_barrel_height,
+            w: 5.,
+        });
+        barrels.push(Rect {
+            x: -3.,
+            y: 0.0 - base_radius - smallest_barrel_height,
+            h: smallest_barrel_height,
+            w: 6.,
+        });
+    }
+    barrels
+}
+fn draw_explosions(main_gun: &MainGun, canvas: &mut Canvas, ctx: &mut Context, current_time: f32) {
+    if main_gun.explosion_info_list.is_empty() {
+        return;
+    }
+    let mut explosion = MeshBuilder::new();
+    for cur_explosion in &main_gun.explosion_info_list {
+        let time_since_explosion = current_time - cur_explosion.started_time;
+        if time_since_explosion < EXPLOSION_GROW_LENGTH {
+            let percentage_through = time_since_explosion / EXPLOSION_GROW_LENGTH;
+            //create and grow yellow circle
+            explosion
+                .circle(
+                    graphics::DrawMode::fill(),
+                    [cur_explosion.x, -cur_explosion.y],
+                    cur_explosion.radius * percentage_through,
+                    0.1,
+                    Color::YELLOW,
+                )
+                .unwrap();
+        } else if time_since_explosion < EXPLOSION_GROW_LENGTH + EXPLOSION_BURN_LENGTH {
+            let percentage_through =
+                (time_since_explosion - EXPLOSION_GROW_LENGTH) / EXPLOSION_BURN_LENGTH;
+            //create red and shrink yellow
+            explosion
+                .circle(
+                    graphics::DrawMode::fill(),
+                    [cur_explosion.x, -cur_explosion.y],
+                    cur_explosion.radius,
+                    0.1,
+                    Color::from_rgb(
+                        220 - (115. * percentage_through) as u8,
+                        20 + (85. * percentage_through) as u8,
+                        60 + (45. * percentage_through) as u8,
+                    ),
+                )
+                .unwrap()
+                .circle(
+                    graphics::DrawMode::fill(),
+                    [cur_explosion.x, -cur_explosion.y],
+                    cur_explosion.radius * (1. - percentage_through),
+                    0.1,
+                    Color::YELLOW,
+                )
+                .unwrap();
+        } else if time_since_explosion < EXPLOSION_ANIMATION_LENGTH {
+            let percentage_through =
+                (time_since_explosion - EXPLOSION_GROW_LENGTH - EXPLOSION_BURN_LENGTH)
+                    / EXPLOSION_FADE_LENGTH;
+            //grow grey circle over while still shrinking
+            explosion
+                .circle(
+                    graphics::DrawMode::fill(),
+                    [cur_explosion.x, -cur_explosion.y],
+                    cur_explosion.radius,
+                    0.1,
+                    Color::from_rgba(
+                        220 - (115. * percentage_through) as u8,
+                        20 + (85. * percentage_through) as u8,
+                        60 + (45. * percentage_through) as u8,
+                        (255. * (1. - percentage_through)) as u8,
+                    ),
+                )
+                .unwrap();
+        }
+    }
+    let mesh_data = explosion.build();
+    if !mesh_data.indices.is_empty() {
+        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
+        canvas.draw(&mesh, DrawParam::default());
+    }
+}
+/// Shells on their way to a target, arcing up from the turret with a shadow tracking the ground
+fn draw_shells_in_flight(
+    main_gun: &MainGun,
+    canvas: &mut Canvas,
+    ctx: &mut Context,
+    current_time: f32,
+) {
+    if main_gun.shells_in_flight.is_empty() {
+        return;
+    }
+    let mut shells = MeshBuilder::new();
+    for shell in &main_gun.shells_in_flight {
+        let progress = shell.progress(current_time);
+        let ground = [shell.x * progress, -shell.y * progress];
+        //height of the arc, highest half way through the flight
+        let height = (std::f32::consts::PI * progress).sin() * 30.;
+        shells
+            .circle(
+                graphics::DrawMode::fill(),
+                ground,
+                2.,
+                0.1,
+                Color::from_rgba(0, 0, 0, 100),
+            )
+            .unwrap()
+            .circle(
+                graphics::DrawMode::fill(),
+                [ground[0], ground[1] - height],
+                2. + height / 15.,
+                0.1,
+                shell_tip_color(shell.shell),
+            )
+            .unwrap();
+    }
+    let mesh_data = shells.build();
+    let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
+    canvas.draw(&mesh, DrawParam::default());
+}
+/// Tip color telling the kinds of shell apart in the loader
+fn shell_tip_color(shell: ShellKind) -> Color {
+    match shell {
+        ShellKind::Standard => Color::YELLOW,
+        ShellKind::HighExplosive => Color::RED,
+        ShellKind::Incendiary => Color::from_rgb(255, 140, 0),
+    }
+}
+fn draw_artillary_round(
+    left_x: f32,
+    bottom_y: f32,
+    scale: f32,
+    shell: ShellKind,
+    ctx: &mut Context,
+    canvas: &mut Canvas,
+) {
+    //builds an artillary round based on the bottom left corner a size ratio
+    let bottom_y = -bottom_y;
+    let width = 1. * scale;
+    let mut artillary_round = MeshBuilder::new();
+    let height_of_casing = -5. * scale;
+    let height_of_neck = -3. * scale;
+    let height_of_tip = -2. * scale;
+    let neck_loss = 0.2 * scale;
+    artillary_round
+        .rectangle(
+            DrawMode::fill(),
+            Rect {
+                x: left_x,
+                y: bottom_y,
+                w: width,
+                h: height_of_casing,
+            },
+            Color::from_rgb(69, 75, 27),
+        )
+        .unwrap()
+        .polygon(
+            DrawMode::fill(),
+            &[
+                [left_x, bottom_y + height_of_casing],
+                [
+                    left_x + neck_loss,
+                    bottom_y + height_of_casing + height_of_neck,
+                ],
+                [
+                    left_x + (width - neck_loss),
+                    bottom_y + height_of_casing + height_of_neck,
+                ],
+                [left_x + width, bottom_y + height_of_casing],
+            ],
+            Color::from_rgb(69, 75, 27),
+        )
+        .unwrap()
+        .polygon(
+            DrawMode::fill(),
+            &[
+                [
+                    left_x + neck_loss,
+                    bottom_y + height_of_casing + height_of_neck,
+                ],
+                [
+                    left_x + (width / 2.),
+                    bottom_y + height_of_casing + height_of_neck + height_of_tip,
+                ],
+                [
+                    left_x + (width - neck_loss),
+                    bottom_y + height_of_casing + height_of_neck,
+                ],
+            ],
+            shell_tip_color(shell),
+        )
+        .unwrap();
+    let mesh_data = artillary_round.build();
+    let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
+    canvas.draw(&mesh, DrawParam::default())
+}
+impl MainGunRender for MainGun {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, current_time: f32) {
+        draw_explosions(self, canvas, ctx, current_time);
+        let mut mesh_builder = MeshBuilder::new();
+        let barrel_positions = get_barrel_segment_positions(self);
+        let mut triangle_opacity = 0;
+        if self.since_fired < 0.05 {
+            triangle_opacity = 255
+        }
+        mesh_builder
+            .circle(
+                graphics::DrawMode::fill(),
+                [0., 0.],
+                10.,
+                0.0001,
+                Color::from_rgb(60, 60, 60),
+            )
+            .unwrap()
+            .rectangle(
+                graphics::DrawMode::fill(),
+                barrel_positions[0],
+                Color::from_rgb(60, 60, 60),
+            )
+            .unwrap()
+            .rectangle(
+                graphics::DrawMode::fill(),
+                barrel_positions[1],
+                Color::from_rgb(60, 60, 60),
+            )
+            .unwrap()
+            .rectangle(
+                graphics::DrawMode::fill(),
+                barrel_positions[2],
+                Color::from_rgb(60, 60, 60),
+            )
+            .unwrap()
+            .rectangle(
+                graphics::DrawMode::fill(),
+                barrel_positions[3],
+                Color::from_rgb(60, 60, 60),
+            )
+            .unwrap()
+            .polygon(
+                graphics::DrawMode::fill(),
+                &[[-2., -32.5], [-2., -30.5], [-8., -31.5]],
+                Color::from_rgba(255, 255, 51, triangle_opacity),
+            )
+            .unwrap()
+            .polygon(
+                graphics::DrawMode::fill(),
+                &[[2., -32.5], [2., -30.5], [8., -31.5]],
+                Color::from_rgba(255, 255, 51, triangle_opacity),
+            )
+            .unwrap();
+        // MAIN GOAL: figure out the positions of each of the barrel segments and draw them
+        let mesh_data = mesh_builder.build();
+        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
+        canvas.draw(&mesh, DrawParam::default().rotation(self.current_rotation));
+        draw_shells_in_flight(self, canvas, ctx, current_time);
+    }
+    fn draw_targets(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        if self.target_info_list.is_empty() {
+            return;
+        }
+        //the blast each queued shell will make and a marker on its aim point
+        let mut markers = MeshBuilder::new();
+        for (index, target) in self.target_info_list.iter().enumerate() {
+            // locked on targets follow their enemy
+            let color = if target.enemy_id.is_some() {
+                Color::from_rgb(255, 140, 0)
+            } else {
+                Color::YELLOW
+            };
+            markers
+                .circle(
+                    DrawMode::stroke(0.5),
+                    [target.x, -target.y],
+                    self.predicted_blast_radius(index),
+                    0.1,
+                    Color::new(color.r, color.g, color.b, 0.6),
+                )
+                .unwrap()
+                .circle(
+                    DrawMode::fill(),
+                    [target.x, -target.y],
+                    TARGET_MARKER_RADIUS / 2.,
+                    0.1,
+                    color,
+                )
+                .unwrap();
+        }
+        let mesh = Mesh::from_data(&ctx.gfx, markers.build());
+        canvas.draw(&mesh, DrawParam::default());
+        //the order they will be fired in
+        for (index, target) in self.target_info_list.iter().enumerate() {
+            let mut number_fragment = TextFragment::new(format!("{}", index + 1));
+            number_fragment.color = Some(Color::BLACK);
+            number_fragment.scale = Some(PxScale::from(40.));
+            let number_text = Text::new(number_fragment);
+            graphics::Drawable::draw(
+                &number_text,
+                canvas,
+                DrawParam::default()
+                    .scale([0.1, 0.1])
+                    .dest([target.x - 1., -target.y - 2.]),
+            );
+        }
+    }
+    fn draw_ammo_loader(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        //let mut ammo_loader = MeshBuilder::new();
+        let mut percentage_through = 0.;
+        canvas.draw(
+            &ggez::graphics::Quad,
+            DrawParam::default()
+                .color(Color::from_rgb(128, 128, 128))
+                .scale([24., 11.5])
+                .dest([0., -5.5]),
+        );
+        if let Some(next_shell) = self
+            .magazine
+            .front()
+            .filter(|_| self.shooting_duration > self.since_fired)
+        {
+            percentage_through = self.since_fired / self.shooting_duration;
+            //this is the next shot comming up
+            draw_artillary_round(
+                22. + (0.75 + (0.25 * percentage_through)),
+                -2.5 - (2.5 * percentage_through),
+                0.5 + (percentage_through / 2.),
+                *next_shell,
+                ctx,
+                canvas,
+            );
+        }
+        // only the rounds actually loaded are drawn, packed towards the breech in firing order
+        for (index, shell) in self.magazine.iter().take(8).enumerate() {
+            draw_artillary_round(
+                23. - (index as f32 * 2.) - (percentage_through * 2.),
+                -5.,
+                1.,
+                *shell,
+                ctx,
+                canvas,
+            );
+        }
+        canvas.draw(
+            &ggez::graphics::Quad,
+            DrawParam::default()
+                .color(Color::BLACK)
+                .scale([3., 12.])
+                .dest([23.5, -6.]),
+        );
+    }
+}
@@ -0,0 +1,7 @@
+//! Drawing for the simulation state, the simulation itself lives in the library
+pub mod building;
+pub mod crafting;
+pub mod enemy;
+pub mod main_gun;
+pub mod sentry;
+pub mod worker;
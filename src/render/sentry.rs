@@ -0,0 +1,68 @@
+use ggez::graphics::{
+    self, Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, PxScale, Rect, Text, TextFragment,
+};
+use ggez::mint::Point2;
+use ggez::Context;
+use the_last_hope::sentry::{Sentry, TargetPriority};
+
+pub trait SentryRender {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas);
+}
+impl SentryRender for Sentry {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        let center = Point2 {
+            x: self.position.0,
+            y: self.position.1,
+        };
+        // the barrel points down the screen at rotation 0, like the enemy triangles
+        let mut mesh_builder = MeshBuilder::new();
+        mesh_builder
+            .circle(
+                DrawMode::fill(),
+                [0., 0.],
+                4.,
+                0.1,
+                Color::from_rgb(30, 30, 35),
+            )
+            .unwrap()
+            .rectangle(
+                DrawMode::fill(),
+                Rect::new(-1., 0., 2., 9.),
+                Color::from_rgb(30, 30, 35),
+            )
+            .unwrap();
+        let mesh = Mesh::from_data(&ctx.gfx, mesh_builder.build());
+        canvas.draw(
+            &mesh,
+            DrawParam::default().rotation(self.rotation).dest(center),
+        );
+        //tracer for the last shot
+        if let Some(shot) = self.last_shot.filter(|_| self.shot_flash > 0.) {
+            let tracer = Mesh::new_line(
+                &ctx.gfx,
+                &[[self.position.0, self.position.1], [shot.0, shot.1]],
+                0.5,
+                Color::YELLOW,
+            )
+            .unwrap();
+            canvas.draw(&tracer, DrawParam::default());
+        }
+        //priority letter
+        let priority_letter = match self.priority {
+            TargetPriority::Nearest => "N",
+            TargetPriority::Weakest => "W",
+            TargetPriority::First => "F",
+        };
+        let mut priority_text_fragment = TextFragment::new(priority_letter);
+        priority_text_fragment.color = Some(Color::WHITE);
+        priority_text_fragment.scale = Some(PxScale::from(40.));
+        let priority_text = Text::new(priority_text_fragment);
+        graphics::Drawable::draw(
+            &priority_text,
+            canvas,
+            DrawParam::default()
+                .scale([0.1, 0.1])
+                .dest([self.position.0 + 3., self.position.1 - 7.]),
+        );
+    }
+}
@@ -0,0 +1,61 @@
+use ggez::{
+    graphics::{Canvas, Color, DrawParam, Mesh, MeshBuilder},
+    Context,
+};
+use the_last_hope::worker::{TaskKind, Worker, WorkerState};
+
+pub trait WorkerRender {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas);
+}
+impl WorkerRender for Worker {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) {
+        let mut mesh_builder = MeshBuilder::new();
+        mesh_builder
+            .circle(
+                ggez::graphics::DrawMode::fill(),
+                [self.position.0 as f32, self.position.1 as f32],
+                5.,
+                0.1,
+                Color::BLUE,
+            )
+            .unwrap();
+        // the body being carried rides on top in its own color, dimmed like a corpse
+        if let Some(body) = &self.carrying {
+            let [r, g, b] = body.color;
+            mesh_builder
+                .circle(
+                    ggez::graphics::DrawMode::fill(),
+                    [self.position.0 as f32, self.position.1 as f32],
+                    2.5,
+                    0.1,
+                    Color::from_rgb(r / 2, g / 2, b / 2),
+                )
+                .unwrap();
+        }
+        let mesh_data = mesh_builder.build();
+        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
+        canvas.draw(&mesh, DrawParam::default());
+    }
+}
+/// What a worker is up to, for the roster
+pub fn activity_text(worker: &Worker) -> &'static str {
+    let Some(task) = &worker.task else {
+        return "IDLE";
+    };
+    if let TaskKind::Repair { .. } = task.kind {
+        return match worker.state {
+            WorkerState::Idle => "IDLE",
+            WorkerState::Travelling { .. } => "TO REPAIR",
+            WorkerState::Working { .. } => "REPAIRING",
+        };
+    }
+    let last_goal = task.goals.len().saturating_sub(1);
+    match worker.state {
+        WorkerState::Idle => "IDLE",
+        WorkerState::Travelling { goal: 0 } => "TO BODY",
+        WorkerState::Working { goal: 0, .. } => "PICKING UP",
+        WorkerState::Travelling { goal } if goal == last_goal => "CARRYING",
+        WorkerState::Working { goal, .. } if goal == last_goal => "DROPPING OFF",
+        WorkerState::Travelling { .. } | WorkerState::Working { .. } => "BUSY",
+    }
+}
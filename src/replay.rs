@@ -0,0 +1,212 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::map::BuildingType;
+use crate::simulation::{Command, Simulation};
+
+/// Seed plus every command with the tick it was applied on, enough to rebuild a whole game
+///
+/// Saved as plain text, one command per line:
+/// ```text
+/// seed 42
+/// 120 fire 10.5 -32
+/// 300 collect -60 12.25
+/// 301 spawn
+/// 400 switch
+/// 410 callwave
+/// 500 place sentry 40 120 0
+/// 520 priority 1
+/// 540 recipe 2
+/// 560 hire
+/// 570 repair 1
+/// 580 cancel 3
+/// 590 autocollect
+/// 600 lockon 12
+/// 610 prioritize 2
+/// 620 untarget 0
+/// 630 cleartargets
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub commands: Vec<(u64, Command)>,
+}
+impl Replay {
+    pub fn new(seed: u64) -> Self {
+        Replay {
+            seed,
+            commands: Vec::new(),
+        }
+    }
+    pub fn record(&mut self, tick: u64, command: Command) {
+        self.commands.push((tick, command));
+    }
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_text())
+    }
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        Replay::from_text(&fs::read_to_string(path)?)
+    }
+    pub fn to_text(&self) -> String {
+        let mut text = format!("seed {}\n", self.seed);
+        for (tick, command) in &self.commands {
+            let line = match command {
+                Command::Fire { x, y } => format!("{} fire {} {}\n", tick, x, y),
+                Command::FireAtEnemy { enemy_id } => format!("{} lockon {}\n", tick, enemy_id),
+                Command::RemoveTarget { index } => format!("{} untarget {}\n", tick, index),
+                Command::PrioritizeTarget { index } => {
+                    format!("{} prioritize {}\n", tick, index)
+                }
+                Command::ClearTargets => format!("{} cleartargets\n", tick),
+                Command::CollectBody { x, y } => format!("{} collect {} {}\n", tick, x, y),
+                Command::SpawnEnemy => format!("{} spawn\n", tick),
+                Command::SwitchPerspective => format!("{} switch\n", tick),
+                Command::CallNextWave => format!("{} callwave\n", tick),
+                Command::PlaceBuilding {
+                    building_type,
+                    x,
+                    y,
+                    rotated,
+                } => format!(
+                    "{} place {} {} {} {}\n",
+                    tick,
+                    building_type.name(),
+                    x,
+                    y,
+                    *rotated as u8
+                ),
+                Command::CycleSentryPriority { building_id } => {
+                    format!("{} priority {}\n", tick, building_id)
+                }
+                Command::CycleRecipe { building_id } => {
+                    format!("{} recipe {}\n", tick, building_id)
+                }
+                Command::HireWorker => format!("{} hire\n", tick),
+                Command::RepairBuilding { building_id } => {
+                    format!("{} repair {}\n", tick, building_id)
+                }
+                Command::CancelTask { task_id } => format!("{} cancel {}\n", tick, task_id),
+                Command::ToggleAutoCollect => format!("{} autocollect\n", tick),
+            };
+            text.push_str(&line);
+        }
+        text
+    }
+    pub fn from_text(text: &str) -> io::Result<Replay> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let seed_line = lines.next().ok_or_else(|| bad_line("<empty file>"))?;
+        let seed = seed_line
+            .strip_prefix("seed ")
+            .and_then(|seed| seed.trim().parse().ok())
+            .ok_or_else(|| bad_line(seed_line))?;
+        let mut replay = Replay::new(seed);
+        for line in lines {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let tick: u64 = field(&parts, 0, line)?;
+            let command = match parts.get(1) {
+                Some(&"fire") => Command::Fire {
+                    x: field(&parts, 2, line)?,
+                    y: field(&parts, 3, line)?,
+                },
+                Some(&"lockon") => Command::FireAtEnemy {
+                    enemy_id: field(&parts, 2, line)?,
+                },
+                Some(&"untarget") => Command::RemoveTarget {
+                    index: field(&parts, 2, line)?,
+                },
+                Some(&"prioritize") => Command::PrioritizeTarget {
+                    index: field(&parts, 2, line)?,
+                },
+                Some(&"cleartargets") => Command::ClearTargets,
+                Some(&"collect") => Command::CollectBody {
+                    x: field(&parts, 2, line)?,
+                    y: field(&parts, 3, line)?,
+                },
+                Some(&"spawn") => Command::SpawnEnemy,
+                Some(&"switch") => Command::SwitchPerspective,
+                Some(&"callwave") => Command::CallNextWave,
+                Some(&"place") => Command::PlaceBuilding {
+                    building_type: match parts.get(2) {
+                        Some(&"sentry") => BuildingType::Sentry,
+                        Some(&"barricade") => BuildingType::Baricade,
+                        Some(&"crafting") => BuildingType::CraftingStation,
+                        _ => return Err(bad_line(line)),
+                    },
+                    x: field(&parts, 3, line)?,
+                    y: field(&parts, 4, line)?,
+                    rotated: match parts.get(5) {
+                        Some(&"0") => false,
+                        Some(&"1") => true,
+                        _ => return Err(bad_line(line)),
+                    },
+                },
+                Some(&"priority") => Command::CycleSentryPriority {
+                    building_id: field(&parts, 2, line)?,
+                },
+                Some(&"recipe") => Command::CycleRecipe {
+                    building_id: field(&parts, 2, line)?,
+                },
+                Some(&"hire") => Command::HireWorker,
+                Some(&"repair") => Command::RepairBuilding {
+                    building_id: field(&parts, 2, line)?,
+                },
+                Some(&"cancel") => Command::CancelTask {
+                    task_id: field(&parts, 2, line)?,
+                },
+                Some(&"autocollect") => Command::ToggleAutoCollect,
+                _ => return Err(bad_line(line)),
+            };
+            replay.record(tick, command);
+        }
+        Ok(replay)
+    }
+}
+fn bad_line(line: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("bad replay line: {}", line),
+    )
+}
+/// Parses the part of a replay line at an index as the type of the field it fills, so ids,
+/// indices and grid spaces have to be whole numbers and "1.7" or "-3" for an id is a bad line
+fn field<T: FromStr>(parts: &[&str], index: usize, line: &str) -> io::Result<T> {
+    parts
+        .get(index)
+        .and_then(|part| part.parse().ok())
+        .ok_or_else(|| bad_line(line))
+}
+/// Feeds a recorded game back into a simulation tick by tick
+pub struct ReplayPlayer {
+    replay: Replay,
+    next_command: usize,
+}
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            next_command: 0,
+        }
+    }
+    /// A fresh simulation started from the recorded seed
+    pub fn new_simulation(&self) -> Simulation {
+        Simulation::with_seed(self.replay.seed)
+    }
+    /// Queues every command recorded for the tick the simulation is about to run
+    pub fn queue_commands(&mut self, simulation: &mut Simulation) {
+        while let Some((tick, command)) = self.replay.commands.get(self.next_command) {
+            if *tick > simulation.tick {
+                break;
+            }
+            simulation.queue_command(*command);
+            self.next_command += 1;
+        }
+    }
+    pub fn is_finished(&self) -> bool {
+        self.next_command >= self.replay.commands.len()
+    }
+}
@@ -1,7 +1,139 @@
-pub struct Enemy {
-    pub(crate) id: String,
-    pub(crate) health: f32,
-    pub(crate) position: (i32, i32),
-    pub(crate) last_rotation: f32,
-    pub(crate) speed: u32,
+use std::f32::consts::PI;
+
+use crate::enemy::Enemy;
+use crate::map::{Building, Map};
+use crate::spatial::SpatialIndex;
+
+/// How far off the barrel can point and still take the shot, in radians
+const AIM_TOLERANCE: f32 = 0.1;
+/// Seconds a shot stays visible as a tracer
+pub const SHOT_FLASH_LENGTH: f32 = 0.08;
+/// Extra path cost a sentry puts on every grid space it covers
+pub const SENTRY_THREAT: u32 = 20;
+/// Which enemy in range a sentry shoots at
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TargetPriority {
+    Nearest,
+    Weakest,
+    /// the enemy with the least path left to any building
+    First,
+}
+impl TargetPriority {
+    pub fn next(&self) -> TargetPriority {
+        match self {
+            TargetPriority::Nearest => TargetPriority::Weakest,
+            TargetPriority::Weakest => TargetPriority::First,
+            TargetPriority::First => TargetPriority::Nearest,
+        }
+    }
+}
+/// Auto firing turret standing on a sentry building
+#[derive(Clone, Debug)]
+pub struct Sentry {
+    pub building_id: u32,
+    /// center of the building in screen space, like enemy positions
+    pub position: (f32, f32),
+    pub range: f32,
+    /// shots per second
+    pub fire_rate: f32,
+    /// radians per second the barrel can turn
+    pub turn_speed: f32,
+    pub damage: f32,
+    pub priority: TargetPriority,
+    /// barrel rotation, 0 points down the screen like `Enemy::rotation`
+    pub rotation: f32,
+    /// seconds until the sentry can fire again
+    pub cooldown: f32,
+    /// where the last shot landed, shown while `shot_flash` runs
+    pub last_shot: Option<(f32, f32)>,
+    pub shot_flash: f32,
+    pub fired_count: u32,
+}
+impl Sentry {
+    pub fn new(building_id: u32, building: &Building) -> Self {
+        Sentry {
+            building_id,
+            position: building.center(),
+            range: 60.,
+            fire_rate: 2.,
+            turn_speed: PI,
+            damage: 15.,
+            priority: TargetPriority::Nearest,
+            rotation: 0.,
+            cooldown: 0.,
+            last_shot: None,
+            shot_flash: 0.,
+            fired_count: 0,
+        }
+    }
+    /// Grid space and radius in grid spaces of the kill zone it puts on the map
+    pub fn kill_zone(&self) -> ((usize, usize), usize) {
+        (
+            Map::convert_position_to_grid_position(self.position),
+            (self.range / 4.) as usize,
+        )
+    }
+    fn distance_to(&self, enemy: &Enemy) -> f32 {
+        let dif_x = enemy.position.0 - self.position.0;
+        let dif_y = enemy.position.1 - self.position.1;
+        (dif_x * dif_x + dif_y * dif_y).sqrt()
+    }
+    /// Index of the enemy in range this sentry wants to shoot
+    pub fn pick_target(
+        &self,
+        enemy_alive_list: &[Enemy],
+        enemy_index: &SpatialIndex,
+        map: &Map,
+    ) -> Option<usize> {
+        let in_range = enemy_index
+            .in_radius(self.position, self.range)
+            .into_iter()
+            .map(|index| (index, &enemy_alive_list[index]))
+            .filter(|(_, enemy)| enemy.health >= 0.);
+        let score = |enemy: &Enemy| -> f32 {
+            match self.priority {
+                TargetPriority::Nearest => self.distance_to(enemy),
+                TargetPriority::Weakest => enemy.health,
+                TargetPriority::First => {
+                    let grid_position = Map::convert_position_to_grid_position(enemy.position);
+                    map.map[grid_position.0][grid_position.1].distance as f32
+                }
+            }
+        };
+        in_range
+            .min_by(|(_, a), (_, b)| score(a).total_cmp(&score(b)))
+            .map(|(index, _)| index)
+    }
+    /// Turns towards the chosen target and shoots it once aimed and reloaded
+    pub fn update(
+        &mut self,
+        time_dif: f32,
+        enemy_alive_list: &mut [Enemy],
+        enemy_index: &SpatialIndex,
+        map: &Map,
+    ) {
+        self.cooldown = (self.cooldown - time_dif).max(0.);
+        self.shot_flash = (self.shot_flash - time_dif).max(0.);
+        let Some(target_index) = self.pick_target(enemy_alive_list, enemy_index, map) else {
+            return;
+        };
+        let target = &mut enemy_alive_list[target_index];
+        let wanted_rotation = Enemy::rotation_of((
+            target.position.0 - self.position.0,
+            target.position.1 - self.position.1,
+        ));
+        // shortest way round, wrapped to -PI..PI
+        let turn = (wanted_rotation - self.rotation + PI).rem_euclid(2. * PI) - PI;
+        let max_turn = self.turn_speed * time_dif;
+        self.rotation = (self.rotation + turn.clamp(-max_turn, max_turn)).rem_euclid(2. * PI);
+        if turn.abs() - max_turn.min(turn.abs()) > AIM_TOLERANCE || self.cooldown > 0. {
+            return;
+        }
+        // hitscan, the shot lands the moment it is fired
+        target.health -= self.damage;
+        self.cooldown = 1. / self.fire_rate;
+        self.last_shot = Some(target.position);
+        self.shot_flash = SHOT_FLASH_LENGTH;
+        self.fired_count += 1;
+    }
 }
@@ -0,0 +1,852 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::f32::consts::PI;
+
+use crate::archetype::ArchetypeRegistry;
+use crate::crafting::{CraftingStation, ShellKind};
+use crate::enemy::{Enemy, STROKE_WIDTH};
+use crate::main_gun::{DamageFalloff, MainGun};
+use crate::map::{Building, BuildingType, Map, PlacementError, Rubble, Terrain, GRID_SIZE};
+use crate::pathing::build_path;
+use crate::replay::Replay;
+use crate::sentry::{Sentry, SENTRY_THREAT};
+use crate::spatial::SpatialIndex;
+use crate::wave::WaveDirector;
+use crate::worker::{
+    JobBoard, Task, TaskKind, TaskPriority, Worker, NEAR_BASE_RADIUS, REPAIR_AMOUNT,
+    STARTING_WORKERS, WORKER_HIRE_COST, WORKER_HOME,
+};
+
+/// Player input the simulation reacts to, applied at the start of the next step
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Command {
+    /// queue a main gun shell on a world position
+    Fire {
+        x: f32,
+        y: f32,
+    },
+    /// queue a main gun shell that follows an enemy and leads it
+    FireAtEnemy {
+        enemy_id: u32,
+    },
+    /// take a queued main gun target out before it is fired at
+    RemoveTarget {
+        index: usize,
+    },
+    /// move a queued main gun target to the front of the queue
+    PrioritizeTarget {
+        index: usize,
+    },
+    /// empty the main gun's target queue
+    ClearTargets,
+    /// send a worker after the body under a world position
+    CollectBody {
+        x: f32,
+        y: f32,
+    },
+    SpawnEnemy,
+    SwitchPerspective,
+    /// skip the rest of the breather and start the next wave now
+    CallNextWave,
+    /// build on the grid, bottom left in screen space grid spaces like `Building`
+    PlaceBuilding {
+        building_type: BuildingType,
+        x: i32,
+        y: i32,
+        /// swap the footprint's width and height
+        rotated: bool,
+    },
+    /// switch a sentry over to its next target priority
+    CycleSentryPriority {
+        building_id: u32,
+    },
+    /// switch a crafting station over to the next kind of shell
+    CycleRecipe {
+        building_id: u32,
+    },
+    /// spend main gun shells on another worker
+    HireWorker,
+    /// send a worker to patch up a damaged building
+    RepairBuilding {
+        building_id: u32,
+    },
+    /// take a task off the job board or away from the worker doing it
+    CancelTask {
+        task_id: u32,
+    },
+    /// switch automatically collecting bodies near the base on or off
+    ToggleAutoCollect,
+}
+/// Which losses end the game, the main building falling always does
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LossConditions {
+    /// lose once every sentry that was built has been destroyed
+    pub all_sentries_lost: bool,
+    /// lose after this many buildings other than the main one were destroyed
+    pub buildings_lost: Option<u32>,
+}
+/// Why a game ended
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LossReason {
+    MainBuildingDestroyed,
+    AllSentriesLost,
+    BuildingsLost(u32),
+}
+/// Everything that makes up a single game, free of any window or graphics state
+pub struct Simulation {
+    pub main_gun: MainGun,
+    pub map: Map,
+    pub last_building_added_id: u32,
+    pub building_hash_map: HashMap<u32, Building>,
+    /// footprints of destroyed buildings
+    pub rubble_list: Vec<Rubble>,
+    /// buildings other than the main one destroyed so far
+    pub buildings_lost: u32,
+    pub sentries_lost: u32,
+    pub loss_conditions: LossConditions,
+    /// turrets keyed by the id of the sentry building they stand on
+    pub sentry_hash_map: HashMap<u32, Sentry>,
+    /// workshops keyed by the id of the crafting station building they stand on
+    pub crafting_hash_map: HashMap<u32, CraftingStation>,
+    pub wave_director: WaveDirector,
+    /// enemy types the waves draw from
+    pub archetypes: ArchetypeRegistry,
+    pub enemy_alive_list: Vec<Enemy>,
+    pub last_enemy_added_id: u32,
+    pub enemy_dead_list: Vec<Enemy>,
+    /// living enemies by where they stand, rebuilt at the start of every step
+    pub enemy_index: SpatialIndex,
+    /// bodies by where they lie, rebuilt at the start of every step
+    pub body_index: SpatialIndex,
+    /// tasks waiting for a free worker
+    pub job_board: JobBoard,
+    /// enemy ids of the bodies a collect task is posted or underway for
+    claimed_bodies: HashSet<u32>,
+    /// queue every body within `auto_collect_radius` of the base without clicking it
+    pub auto_collect: bool,
+    pub auto_collect_radius: f32,
+    pub worker_list: Vec<Worker>,
+    pub last_worker_added_id: u32,
+    pub rooftop_view: bool,
+    pub shake_meter: u8,
+    pub shake_offset: (f32, f32),
+    pub time_since_start: f32,
+    pub tick: u64,
+    /// seed the rng was started from, a seed plus the same commands replays the same game
+    pub seed: u64,
+    rng: StdRng,
+    command_queue: Vec<Command>,
+    /// every command applied so far, tagged with the tick it ran on
+    pub replay: Replay,
+}
+impl Default for Simulation {
+    fn default() -> Self {
+        Simulation::with_seed(rand::random())
+    }
+}
+impl Simulation {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn with_seed(seed: u64) -> Self {
+        //The health for the player/ main building/ main gun
+        // width = 18
+        // height =  12
+        // bottom left x = 116
+        // bottom left y = 131 (screen space, spans up to 119)
+        let mut building_hash_map: HashMap<u32, Building> = HashMap::new();
+        let main_building = Building {
+            bottom_left: (116, 131),
+            width: 18,
+            height: 12,
+            max_health: 1000.,
+            health: 1000.,
+            building_type: BuildingType::Sentry,
+        };
+        building_hash_map.insert(0, main_building);
+        let worker_list: Vec<Worker> = (1..=STARTING_WORKERS)
+            .map(|worker_id| Worker::new(worker_id, WORKER_HOME))
+            .collect();
+        Simulation {
+            worker_list,
+            last_worker_added_id: STARTING_WORKERS,
+            job_board: JobBoard::default(),
+            claimed_bodies: HashSet::new(),
+            auto_collect: false,
+            auto_collect_radius: 200.,
+            last_building_added_id: 0,
+            map: Map::new(&building_hash_map),
+            building_hash_map,
+            sentry_hash_map: HashMap::new(),
+            crafting_hash_map: HashMap::new(),
+            rubble_list: Vec::new(),
+            buildings_lost: 0,
+            sentries_lost: 0,
+            loss_conditions: LossConditions::default(),
+            wave_director: WaveDirector::default(),
+            archetypes: ArchetypeRegistry::default(),
+            main_gun: MainGun {
+                shooting_duration: 2.,
+                enabled: true,
+                rotation_speed_per_second: PI / 10.0,
+                shell_explosive_radius: 50.,
+                damage: 100.,
+                magazine: VecDeque::from(vec![ShellKind::Standard; 8]),
+                max_ammo: 8,
+                rounds_per_corpse_value: 0.5,
+                muzzle_velocity: 150.,
+                damage_falloff: DamageFalloff::Linear,
+                edge_damage: 0.25,
+                max_targets: 6,
+                ..Default::default()
+            },
+            enemy_alive_list: Vec::new(),
+            last_enemy_added_id: 0,
+            enemy_dead_list: Vec::new(),
+            enemy_index: SpatialIndex::default(),
+            body_index: SpatialIndex::default(),
+            rooftop_view: true,
+            shake_meter: 0,
+            shake_offset: (0., 0.),
+            time_since_start: 0.,
+            tick: 0,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            command_queue: Vec::new(),
+            replay: Replay::new(seed),
+        }
+    }
+    /// Queues a command to be applied at the start of the next step
+    pub fn queue_command(&mut self, command: Command) {
+        self.command_queue.push(command);
+    }
+    /// The building a placement would create, or why it can not go there
+    pub fn plan_building(
+        &self,
+        building_type: BuildingType,
+        bottom_left: (i32, i32),
+        rotated: bool,
+    ) -> Result<Building, PlacementError> {
+        let (mut width, mut height) = building_type.footprint();
+        if rotated {
+            std::mem::swap(&mut width, &mut height);
+        }
+        let grid_range = 0..=GRID_SIZE as i32;
+        if !grid_range.contains(&bottom_left.0) || !grid_range.contains(&bottom_left.1) {
+            return Err(PlacementError::OutOfBounds);
+        }
+        let building = Building {
+            building_type,
+            bottom_left: (bottom_left.0 as usize, bottom_left.1 as usize),
+            width,
+            height,
+            max_health: building_type.max_health(),
+            health: building_type.max_health(),
+        };
+        self.map.check_placement(&building)?;
+        Ok(building)
+    }
+    /// Checks and places a new building, returning its id
+    pub fn place_building(
+        &mut self,
+        building_type: BuildingType,
+        bottom_left: (i32, i32),
+        rotated: bool,
+    ) -> Result<u32, PlacementError> {
+        let building = self.plan_building(building_type, bottom_left, rotated)?;
+        Ok(self.add_building(building))
+    }
+    /// Adds a building under the next free id and reroutes the enemies around it, unchecked
+    pub fn add_building(&mut self, building: Building) -> u32 {
+        self.last_building_added_id += 1;
+        let building_id = self.last_building_added_id;
+        self.map.add_building(building_id, &building);
+        if matches!(building.building_type, BuildingType::Sentry) {
+            let sentry = Sentry::new(building_id, &building);
+            let (center, radius) = sentry.kill_zone();
+            self.map.add_kill_zone(center, radius, SENTRY_THREAT);
+            self.sentry_hash_map.insert(building_id, sentry);
+        }
+        if matches!(building.building_type, BuildingType::CraftingStation) {
+            let crafting_station = CraftingStation::new(building_id, &building);
+            self.crafting_hash_map.insert(building_id, crafting_station);
+        }
+        self.building_hash_map.insert(building_id, building);
+        building_id
+    }
+    pub fn remove_building(&mut self, building_id: u32) -> Option<Building> {
+        let building = self.building_hash_map.remove(&building_id)?;
+        self.map.remove_building(&building);
+        if let Some(sentry) = self.sentry_hash_map.remove(&building_id) {
+            let (center, radius) = sentry.kill_zone();
+            self.map.remove_kill_zone(center, radius, SENTRY_THREAT);
+        }
+        // bodies waiting at a lost station are lost with it
+        self.crafting_hash_map.remove(&building_id);
+        Some(building)
+    }
+    pub fn resize_building(&mut self, building_id: u32, width: usize, height: usize) {
+        let Some(building) = self.building_hash_map.get_mut(&building_id) else {
+            return;
+        };
+        let old_building = building.clone();
+        building.width = width;
+        building.height = height;
+        self.map
+            .resize_building(building_id, &old_building, building);
+        // the turret stays centered on its building
+        if let Some(sentry) = self.sentry_hash_map.get_mut(&building_id) {
+            let (center, radius) = sentry.kill_zone();
+            self.map.remove_kill_zone(center, radius, SENTRY_THREAT);
+            sentry.position = building.center();
+            let (center, radius) = sentry.kill_zone();
+            self.map.add_kill_zone(center, radius, SENTRY_THREAT);
+        }
+        if let Some(crafting_station) = self.crafting_hash_map.get_mut(&building_id) {
+            crafting_station.position = building.center();
+        }
+    }
+    /// Id of the building covering a screen space position
+    pub fn building_at(&self, position: (f32, f32)) -> Option<u32> {
+        if !(-500.0..500.).contains(&position.0) || !(-500.0..500.).contains(&position.1) {
+            return None;
+        }
+        let grid_position = Map::convert_position_to_grid_position(position);
+        self.map.map[grid_position.0][grid_position.1]
+            .building
+            .as_ref()
+            .map(|building| building.id)
+    }
+    /// Id of the living enemy under a screen space position
+    pub fn enemy_at(&self, position: (f32, f32)) -> Option<u32> {
+        self.enemy_alive_list
+            .iter()
+            .find(|enemy| enemy.get_hitbox().contains(position))
+            .map(|enemy| enemy.id)
+    }
+    /// Why the game is lost, None while it goes on
+    pub fn loss_reason(&self) -> Option<LossReason> {
+        if self.building_hash_map.get(&0).unwrap().health <= 0. {
+            return Some(LossReason::MainBuildingDestroyed);
+        }
+        if self.loss_conditions.all_sentries_lost
+            && self.sentries_lost > 0
+            && self.sentry_hash_map.is_empty()
+        {
+            return Some(LossReason::AllSentriesLost);
+        }
+        match self.loss_conditions.buildings_lost {
+            Some(limit) if self.buildings_lost >= limit => Some(LossReason::BuildingsLost(limit)),
+            _ => None,
+        }
+    }
+    pub fn is_over(&self) -> bool {
+        self.loss_reason().is_some()
+    }
+    /// Knocks a building down to rubble, the main building stays standing for the loss check
+    pub fn destroy_building(&mut self, building_id: u32) {
+        if building_id == 0 {
+            return;
+        }
+        let Some(building) = self.remove_building(building_id) else {
+            return;
+        };
+        self.buildings_lost += 1;
+        if matches!(building.building_type, BuildingType::Sentry) {
+            self.sentries_lost += 1;
+        }
+        self.map.set_terrain(
+            building.bottom_left,
+            building.width,
+            building.height,
+            Terrain::Rubble,
+        );
+        self.rubble_list.push(Rubble {
+            building_type: building.building_type,
+            bottom_left: building.bottom_left,
+            width: building.width,
+            height: building.height,
+        });
+    }
+    /// Advances the game by one simulation step of `time_dif` seconds
+    pub fn step(&mut self, time_dif: f32) {
+        self.hand_out_enemy_ids();
+        // only spawning adds enemies until they move at the end of the step
+        self.enemy_index.rebuild(&self.enemy_alive_list);
+        self.body_index.rebuild(&self.enemy_dead_list);
+        for command in std::mem::take(&mut self.command_queue) {
+            self.replay.record(self.tick, command);
+            self.apply_command(command);
+        }
+        self.time_since_start += time_dif;
+        self.tick += 1;
+        for _ in 0..self.wave_director.update(time_dif) {
+            self.spawn_enemy();
+        }
+        self.shake_offset = self.generate_shake_offset();
+        self.main_gun
+            .lead_targets(self.time_since_start, &self.enemy_alive_list, &self.map);
+        self.main_gun.update(
+            self.time_since_start,
+            &mut self.enemy_alive_list,
+            &self.enemy_index,
+            &mut self.shake_meter,
+        );
+        self.manage_sentries(time_dif);
+        self.manage_workers(time_dif);
+        self.manage_crafting(time_dif);
+        self.manage_enemies(time_dif);
+    }
+    fn apply_command(&mut self, command: Command) {
+        match command {
+            Command::Fire { x, y } => self.main_gun.initiate_fire_sequence(x, y),
+            Command::FireAtEnemy { enemy_id } => {
+                if let Some(enemy) = self
+                    .enemy_alive_list
+                    .iter()
+                    .find(|enemy| enemy.id == enemy_id)
+                {
+                    self.main_gun.lock_on(enemy);
+                }
+            }
+            Command::RemoveTarget { index } => {
+                self.main_gun.remove_target(index);
+            }
+            Command::PrioritizeTarget { index } => self.main_gun.move_target_to_front(index),
+            Command::ClearTargets => self.main_gun.target_info_list.clear(),
+            Command::CollectBody { x, y } => self.initiate_task(x, y),
+            Command::SpawnEnemy => self.spawn_enemy(),
+            Command::SwitchPerspective => self.switch_perspective(),
+            Command::CallNextWave => self.wave_director.call_next_wave(),
+            Command::PlaceBuilding {
+                building_type,
+                x,
+                y,
+                rotated,
+            } => {
+                // refused placements change nothing, the binary tells the player why
+                let _ = self.place_building(building_type, (x, y), rotated);
+            }
+            Command::CycleSentryPriority { building_id } => {
+                if let Some(sentry) = self.sentry_hash_map.get_mut(&building_id) {
+                    sentry.priority = sentry.priority.next();
+                }
+            }
+            Command::CycleRecipe { building_id } => {
+                if let Some(crafting_station) = self.crafting_hash_map.get_mut(&building_id) {
+                    crafting_station.recipe = crafting_station.recipe.next();
+                }
+            }
+            Command::HireWorker => {
+                self.hire_worker();
+            }
+            Command::RepairBuilding { building_id } => {
+                self.post_repair_task(building_id);
+            }
+            Command::CancelTask { task_id } => self.cancel_task(task_id),
+            Command::ToggleAutoCollect => self.auto_collect = !self.auto_collect,
+        }
+    }
+    fn switch_perspective(&mut self) {
+        self.rooftop_view = !self.rooftop_view;
+        self.main_gun.enabled = self.rooftop_view;
+    }
+    pub fn spawn_enemy(&mut self) {
+        let archetype = self
+            .archetypes
+            .pick(self.wave_director.wave, &mut self.rng)
+            .clone();
+        let health_ratio: f32 = self.rng.gen_range(0.0..1.);
+        let [min_health, max_health] = archetype.health;
+        let base_health: f32 = (min_health + (max_health - min_health) * health_ratio)
+            * self.wave_director.health_multiplier();
+        let random_side = self.wave_director.pick_side(&mut self.rng) + 1;
+        let mut random_side_length = 500. * self.rng.gen_range(0.0..1.);
+        if self.rng.gen() {
+            random_side_length = -random_side_length;
+        }
+        let position_generated: (f32, f32) = match random_side {
+            1 => (-500., random_side_length),
+            2 => (500., random_side_length),
+            3 => (random_side_length, -500.),
+            4 => (random_side_length, 500.),
+            _ => {
+                panic!()
+            }
+        };
+        let mut new_enemy = Enemy::from_archetype(&archetype, position_generated, base_health);
+        self.last_enemy_added_id += 1;
+        new_enemy.id = self.last_enemy_added_id;
+        self.enemy_index.push(&new_enemy);
+        self.enemy_alive_list.push(new_enemy);
+    }
+    /// Gives enemies and bodies pushed onto the lists from outside the simulation an id of their own
+    fn hand_out_enemy_ids(&mut self) {
+        for enemy in self
+            .enemy_alive_list
+            .iter_mut()
+            .chain(self.enemy_dead_list.iter_mut())
+            .filter(|enemy| enemy.id == 0)
+        {
+            self.last_enemy_added_id += 1;
+            enemy.id = self.last_enemy_added_id;
+        }
+    }
+    fn generate_shake_offset(&mut self) -> (f32, f32) {
+        if self.shake_meter > 0 {
+            let current_shake_meter: f32 = self.shake_meter as f32;
+            let gen_x: f32 = self.rng.gen();
+            let gen_y: f32 = self.rng.gen();
+            self.shake_meter -= 1;
+            (
+                (gen_x * current_shake_meter) - (current_shake_meter / 2.),
+                (gen_y * current_shake_meter) - (current_shake_meter / 2.),
+            )
+        } else {
+            (0., 0.)
+        }
+    }
+    fn manage_sentries(&mut self, time_dif: f32) {
+        // walk the sentries in id order so shared targets are shot the same way every replay
+        let mut sentry_ids: Vec<u32> = self.sentry_hash_map.keys().copied().collect();
+        sentry_ids.sort();
+        for sentry_id in sentry_ids {
+            self.sentry_hash_map.get_mut(&sentry_id).unwrap().update(
+                time_dif,
+                &mut self.enemy_alive_list,
+                &self.enemy_index,
+                &self.map,
+            );
+        }
+    }
+    fn manage_enemies(&mut self, time_dif: f32) {
+        let mut destroyed_buildings: Vec<u32> = Vec::new();
+        if !self.enemy_alive_list.is_empty() {
+            let mut current_enemy_index: usize = self.enemy_alive_list.len() - 1;
+            //check enemies for abnomalities and spawn
+            loop {
+                let current_enemy = &mut self.enemy_alive_list[current_enemy_index];
+                if current_enemy.health < 0. {
+                    //put the dead enemies in the deadlist
+                    self.enemy_dead_list
+                        .push(self.enemy_alive_list.remove(current_enemy_index));
+                } else {
+                    current_enemy.update(time_dif, &self.map);
+                    //enemies at a building wall chip away at it until one of them dies
+                    if let Some(building_id) = current_enemy.building_hit {
+                        let building = self.building_hash_map.get_mut(&building_id).unwrap();
+                        building.health -= current_enemy.attack_damage * time_dif;
+                        if building.health <= 0. && !destroyed_buildings.contains(&building_id) {
+                            destroyed_buildings.push(building_id);
+                        }
+                    }
+                }
+                if current_enemy_index == 0 {
+                    break;
+                }
+                current_enemy_index -= 1;
+            }
+        }
+        //destroyed buildings leave the grid and the flow path opens back up through them
+        for building_id in destroyed_buildings {
+            self.destroy_building(building_id);
+        }
+    }
+    /// Pays for a new worker out of the main gun's loader, None when there are not enough shells
+    pub fn hire_worker(&mut self) -> Option<u32> {
+        if self.main_gun.ammo() < WORKER_HIRE_COST {
+            return None;
+        }
+        // the most recently loaded shells are spent first
+        for _ in 0..WORKER_HIRE_COST {
+            self.main_gun.magazine.pop_back();
+        }
+        self.last_worker_added_id += 1;
+        let worker_id = self.last_worker_added_id;
+        self.worker_list.push(Worker::new(worker_id, WORKER_HOME));
+        Some(worker_id)
+    }
+    fn manage_workers(&mut self, time_dif: f32) {
+        for worker_index in (0..self.worker_list.len()).rev() {
+            if self.worker_list[worker_index].health < 0. {
+                // a fallen worker drops the body it was carrying
+                self.abandon_task(worker_index);
+                self.worker_list.remove(worker_index);
+            }
+        }
+        if self.auto_collect {
+            self.auto_collect_bodies();
+        }
+        self.assign_tasks();
+        for worker_index in (0..self.worker_list.len()).rev() {
+            if let Some(goal) = self.worker_list[worker_index].update(time_dif) {
+                self.finish_goal(worker_index, goal);
+            }
+        }
+    }
+    /// Hands the most urgent tasks on the job board to the closest free workers
+    fn assign_tasks(&mut self) {
+        while self.worker_list.iter().any(|worker| worker.is_available()) {
+            let Some(task) = self.job_board.take_next() else {
+                return;
+            };
+            let goal = task.goals[0];
+            let distance = |worker: &Worker| {
+                let dif_x = worker.position.0 as f32 - goal.0;
+                let dif_y = worker.position.1 as f32 - goal.1;
+                dif_x * dif_x + dif_y * dif_y
+            };
+            let worker_index = self
+                .worker_list
+                .iter()
+                .enumerate()
+                .filter(|(_, worker)| worker.is_available())
+                .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)).then(a.id.cmp(&b.id)))
+                .map(|(worker_index, _)| worker_index)
+                .unwrap();
+            self.worker_list[worker_index].task = Some(task);
+            self.start_goal(worker_index, 0);
+        }
+    }
+    /// Takes a task off the job board, or away from the worker doing it
+    pub fn cancel_task(&mut self, task_id: u32) {
+        if let Some(task) = self.job_board.cancel(task_id) {
+            self.release_claim(&task);
+            return;
+        }
+        let worker_index = self
+            .worker_list
+            .iter()
+            .position(|worker| matches!(&worker.task, Some(task) if task.id == task_id));
+        if let Some(worker_index) = worker_index {
+            self.abandon_task(worker_index);
+        }
+    }
+    /// Every task posted or underway, on the board first
+    fn all_tasks(&self) -> impl Iterator<Item = &Task> {
+        self.job_board.tasks.iter().chain(
+            self.worker_list
+                .iter()
+                .filter_map(|worker| worker.task.as_ref()),
+        )
+    }
+    /// Id of the task for the body or building under a screen space position
+    pub fn task_at(&self, position: (f32, f32)) -> Option<u32> {
+        let building_id = self.building_at(position);
+        self.all_tasks()
+            .find(|task| match task.kind {
+                TaskKind::Repair { building_id: id } => Some(id) == building_id,
+                TaskKind::CollectBody { body_id } => self
+                    .enemy_dead_list
+                    .iter()
+                    .any(|body| body.id == body_id && body.get_hitbox().contains(position)),
+            })
+            .map(|task| task.id)
+    }
+    /// Plans a worker's walk to one of its task's goals, the task is given up when there is no way there
+    fn start_goal(&mut self, worker_index: usize, goal: usize) {
+        let worker = &mut self.worker_list[worker_index];
+        let goal_position = worker.task.as_ref().unwrap().goals[goal];
+        let start_position = (worker.position.0 as f32, worker.position.1 as f32);
+        // workers route around buildings with A* instead of the enemies' flow path
+        match build_path(&self.map, start_position, goal_position) {
+            Some(path) => worker.travel_to(goal, path),
+            None => self.abandon_task(worker_index),
+        }
+    }
+    /// Carries out what happens at a goal once the worker's time there is up and sends it on to the next
+    fn finish_goal(&mut self, worker_index: usize, goal: usize) {
+        let task = self.worker_list[worker_index].task.clone().unwrap();
+        if let TaskKind::Repair { building_id } = task.kind {
+            // the building may have fallen while the worker was on the way
+            if let Some(building) = self.building_hash_map.get_mut(&building_id) {
+                building.health = (building.health + REPAIR_AMOUNT).min(building.max_health);
+            }
+            self.worker_list[worker_index].finish_task();
+            return;
+        }
+        if goal == 0 {
+            // the body is off the field either way, someone else may have got to it first
+            self.release_claim(&task);
+            let body = match task.kind {
+                TaskKind::CollectBody { body_id } => self.pick_up_body(body_id),
+                TaskKind::Repair { .. } => None,
+            };
+            let Some(body) = body else {
+                self.worker_list[worker_index].finish_task();
+                return;
+            };
+            self.worker_list[worker_index].carrying = Some(body);
+        }
+        if goal + 1 < task.goals.len() {
+            self.start_goal(worker_index, goal + 1);
+            return;
+        }
+        if let Some(body) = self.worker_list[worker_index].carrying.take() {
+            self.deposit_body(&task, body);
+        }
+        self.worker_list[worker_index].finish_task();
+    }
+    /// Gives up a worker's task, leaving anything it carried on the ground where it stands
+    fn abandon_task(&mut self, worker_index: usize) {
+        let worker = &mut self.worker_list[worker_index];
+        match worker.carrying.take() {
+            Some(mut body) => {
+                body.position = (worker.position.0 as f32, worker.position.1 as f32);
+                self.enemy_dead_list.push(body);
+            }
+            // the body was never picked up and can be collected again
+            None => {
+                if let Some(task) = worker.task.take() {
+                    self.release_claim(&task);
+                }
+            }
+        }
+        self.worker_list[worker_index].finish_task();
+    }
+    /// Takes the body of the enemy with an id off the field
+    fn pick_up_body(&mut self, body_id: u32) -> Option<Enemy> {
+        let body_index = self
+            .enemy_dead_list
+            .iter()
+            .position(|body| body.id == body_id)?;
+        Some(self.enemy_dead_list.remove(body_index))
+    }
+    /// Hands a delivered body to its crafting station, or straight to the main gun when the
+    /// task has none or the station is gone
+    fn deposit_body(&mut self, task: &Task, body: Enemy) {
+        match task
+            .deposit_at
+            .and_then(|building_id| self.crafting_hash_map.get_mut(&building_id))
+        {
+            Some(crafting_station) => crafting_station.deposit(body.size),
+            None => self.main_gun.load_corpse(body.corpse_value),
+        }
+    }
+    fn manage_crafting(&mut self, time_dif: f32) {
+        // stations hand shells over in id order so a nearly full loader fills the same way every replay
+        let mut crafting_ids: Vec<u32> = self.crafting_hash_map.keys().copied().collect();
+        crafting_ids.sort();
+        for crafting_id in crafting_ids {
+            self.crafting_hash_map
+                .get_mut(&crafting_id)
+                .unwrap()
+                .update(time_dif, &mut self.main_gun);
+        }
+    }
+    /// Id and drop off position of the crafting station closest to a screen space position
+    fn nearest_crafting_station(&self, position: (f32, f32)) -> Option<(u32, (f32, f32))> {
+        let distance = |station: &CraftingStation| {
+            let dif_x = station.position.0 - position.0;
+            let dif_y = station.position.1 - position.1;
+            dif_x * dif_x + dif_y * dif_y
+        };
+        self.crafting_hash_map
+            .values()
+            .min_by(|a, b| {
+                distance(a)
+                    .total_cmp(&distance(b))
+                    .then(a.building_id.cmp(&b.building_id))
+            })
+            .map(|station| (station.building_id, station.position))
+    }
+    fn initiate_task(&mut self, mouse_x: f32, mouse_y: f32) {
+        let mouse_y = -mouse_y;
+        if self.rooftop_view {
+            return;
+        }
+        // hitboxes reach out from the body by up to 2.5 times its size with the stroke
+        let reach = (self.body_index.largest_size + STROKE_WIDTH) * 2.5;
+        let clicked_body = self
+            .body_index
+            .in_rect(
+                (mouse_x - reach, mouse_y - reach),
+                (mouse_x + reach, mouse_y + reach),
+            )
+            .into_iter()
+            .map(|body_index| &self.enemy_dead_list[body_index])
+            .find(|bad_guy| {
+                !self.is_claimed(bad_guy) && bad_guy.get_hitbox().contains((mouse_x, mouse_y))
+            })
+            .map(|bad_guy| (bad_guy.id, bad_guy.position));
+        // a dead enemy was clicked
+        if let Some((body_id, body_position)) = clicked_body {
+            self.post_collect_task(body_id, body_position);
+        }
+    }
+    /// Whether a body already has a worker on its way or a task posted for it
+    fn is_claimed(&self, body: &Enemy) -> bool {
+        self.claimed_bodies.contains(&body.id)
+    }
+    /// Frees the body a collect task was for, once it is picked up or the task is given up
+    fn release_claim(&mut self, task: &Task) {
+        if let TaskKind::CollectBody { body_id } = task.kind {
+            self.claimed_bodies.remove(&body_id);
+        }
+    }
+    /// Posts a task to carry a body lying at a position to the drop off
+    fn post_collect_task(&mut self, body_id: u32, body_position: (f32, f32)) -> u32 {
+        let time_to_collect_body: f32 = 0.5;
+        let time_to_deposit_body: f32 = 0.5;
+        // bodies go to the closest crafting station, or home to the main gun without one
+        let home_cord: (f32, f32) = (WORKER_HOME.0 as f32, WORKER_HOME.1 as f32);
+        let crafting_station = self.nearest_crafting_station(body_position);
+        let distance_from_base = ((body_position.0 - home_cord.0).powi(2)
+            + (body_position.1 - home_cord.1).powi(2))
+        .sqrt();
+        let priority = if distance_from_base <= NEAR_BASE_RADIUS {
+            TaskPriority::CollectNearBase
+        } else {
+            TaskPriority::CollectFar
+        };
+        self.claimed_bodies.insert(body_id);
+        self.job_board.post(Task {
+            id: 0,
+            kind: TaskKind::CollectBody { body_id },
+            priority,
+            task_times: vec![time_to_collect_body, time_to_deposit_body],
+            goals: vec![
+                body_position,
+                crafting_station.map_or(home_cord, |(_, position)| position),
+            ],
+            deposit_at: crafting_station.map(|(building_id, _)| building_id),
+        })
+    }
+    /// Posts a task to patch up a damaged building, None when it is at full health or already queued
+    pub fn post_repair_task(&mut self, building_id: u32) -> Option<u32> {
+        let building = self.building_hash_map.get(&building_id)?;
+        let already_queued = self.all_tasks().any(
+            |task| matches!(task.kind, TaskKind::Repair { building_id: id } if id == building_id),
+        );
+        if building.health >= building.max_health || already_queued {
+            return None;
+        }
+        let time_to_repair: f32 = 2.;
+        let building_center = building.center();
+        Some(self.job_board.post(Task {
+            id: 0,
+            kind: TaskKind::Repair { building_id },
+            priority: TaskPriority::Repair,
+            task_times: vec![time_to_repair],
+            goals: vec![building_center],
+            deposit_at: None,
+        }))
+    }
+    /// Posts collection tasks for every unclaimed body within the auto collect radius of the base
+    fn auto_collect_bodies(&mut self) {
+        let home_cord = (WORKER_HOME.0 as f32, WORKER_HOME.1 as f32);
+        // bodies dropped since the step started are only indexed next step, nothing was removed yet
+        let bodies: Vec<(u32, (f32, f32))> = self
+            .body_index
+            .in_radius(home_cord, self.auto_collect_radius)
+            .into_iter()
+            .map(|body_index| &self.enemy_dead_list[body_index])
+            .filter(|body| !self.is_claimed(body))
+            .map(|body| (body.id, body.position))
+            .collect();
+        for (body_id, body_position) in bodies {
+            self.post_collect_task(body_id, body_position);
+        }
+    }
+}
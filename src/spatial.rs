@@ -0,0 +1,160 @@
+use crate::enemy::Enemy;
+use crate::map::{Map, GRID_SIZE};
+
+/// Map grid spaces along each side of a bucket
+pub const BUCKET_GRID_SPACES: usize = 8;
+/// World units along each side of a bucket
+const BUCKET_SIZE: f32 = BUCKET_GRID_SPACES as f32 * 4.;
+/// Buckets along each side of the map
+const BUCKETS: usize = GRID_SIZE.div_ceil(BUCKET_GRID_SPACES);
+
+/// Uniform grid of buckets over the map's grid spaces, holding the index of every enemy of a list
+/// by where it stands so nearby enemies are found without walking the whole list
+///
+/// Positions are in screen space like `Enemy::position`, and the index has to be rebuilt with
+/// `rebuild` whenever enemies in the list move or are removed
+#[derive(Clone, Debug)]
+pub struct SpatialIndex {
+    buckets: Vec<Vec<usize>>,
+    positions: Vec<(f32, f32)>,
+    /// largest enemy size indexed, queries for hitboxes reaching into an area pad by it
+    pub largest_size: f32,
+    /// largest `Enemy::body_radius` indexed, queries for bodies reaching into an area pad by it
+    pub largest_body_radius: f32,
+}
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        SpatialIndex {
+            buckets: vec![Vec::new(); BUCKETS * BUCKETS],
+            positions: Vec::new(),
+            largest_size: 0.,
+            largest_body_radius: 0.,
+        }
+    }
+}
+impl SpatialIndex {
+    pub fn new(enemy_list: &[Enemy]) -> Self {
+        let mut spatial_index = SpatialIndex::default();
+        spatial_index.rebuild(enemy_list);
+        spatial_index
+    }
+    /// Forgets everything and indexes the enemies of a list again
+    pub fn rebuild(&mut self, enemy_list: &[Enemy]) {
+        for bucket in &mut self.buckets {
+            bucket.clear();
+        }
+        self.positions.clear();
+        self.largest_size = 0.;
+        self.largest_body_radius = 0.;
+        for enemy in enemy_list {
+            self.push(enemy);
+        }
+    }
+    /// Indexes an enemy pushed onto the end of the list
+    pub fn push(&mut self, enemy: &Enemy) {
+        let bucket = SpatialIndex::bucket_of(enemy.position);
+        self.buckets[bucket.0 * BUCKETS + bucket.1].push(self.positions.len());
+        self.positions.push(enemy.position);
+        self.largest_size = self.largest_size.max(enemy.size);
+        self.largest_body_radius = self.largest_body_radius.max(enemy.body_radius());
+    }
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+    /// Bucket holding a screen space position, positions off the map go to the nearest edge bucket
+    fn bucket_of(position: (f32, f32)) -> (usize, usize) {
+        let clamped = (position.0.clamp(-500., 500.), position.1.clamp(-500., 500.));
+        let grid_position = Map::convert_position_to_grid_position(clamped);
+        (
+            grid_position.0 / BUCKET_GRID_SPACES,
+            grid_position.1 / BUCKET_GRID_SPACES,
+        )
+    }
+    /// Indices of the enemies in the buckets overlapping a rectangle, some may stand outside it
+    fn candidates(&self, min: (f32, f32), max: (f32, f32)) -> impl Iterator<Item = usize> + '_ {
+        let min_bucket = SpatialIndex::bucket_of(min);
+        let max_bucket = SpatialIndex::bucket_of(max);
+        (min_bucket.0..=max_bucket.0).flat_map(move |bucket_x| {
+            (min_bucket.1..=max_bucket.1)
+                .flat_map(move |bucket_y| self.buckets[bucket_x * BUCKETS + bucket_y].iter())
+                .copied()
+        })
+    }
+    /// Indices of the enemies standing within a radius of a position, in list order
+    pub fn in_radius(&self, center: (f32, f32), radius: f32) -> Vec<usize> {
+        let mut found: Vec<usize> = self
+            .candidates(
+                (center.0 - radius, center.1 - radius),
+                (center.0 + radius, center.1 + radius),
+            )
+            .filter(|index| {
+                let position = self.positions[*index];
+                let dif_x = position.0 - center.0;
+                let dif_y = position.1 - center.1;
+                dif_x * dif_x + dif_y * dif_y <= radius * radius
+            })
+            .collect();
+        found.sort_unstable();
+        found
+    }
+    /// Indices of the enemies standing inside a rectangle, edges included, in list order
+    pub fn in_rect(&self, min: (f32, f32), max: (f32, f32)) -> Vec<usize> {
+        let mut found: Vec<usize> = self
+            .candidates(min, max)
+            .filter(|index| {
+                let position = self.positions[*index];
+                (min.0..=max.0).contains(&position.0) && (min.1..=max.1).contains(&position.1)
+            })
+            .collect();
+        found.sort_unstable();
+        found
+    }
+    /// Indices of the `count` enemies closest to a position, nearest first and ties in list order
+    pub fn nearest(&self, position: (f32, f32), count: usize) -> Vec<usize> {
+        if count == 0 {
+            return Vec::new();
+        }
+        let distance_squared = |index: usize| {
+            let other = self.positions[index];
+            (other.0 - position.0).powi(2) + (other.1 - position.1).powi(2)
+        };
+        let center = SpatialIndex::bucket_of(position);
+        let mut found: Vec<(f32, usize)> = Vec::new();
+        // search ring after ring of buckets, anything past ring r is at least r buckets away
+        for ring in 0..BUCKETS {
+            let min = (center.0.saturating_sub(ring), center.1.saturating_sub(ring));
+            let max = (
+                (center.0 + ring).min(BUCKETS - 1),
+                (center.1 + ring).min(BUCKETS - 1),
+            );
+            for bucket_x in min.0..=max.0 {
+                for bucket_y in min.1..=max.1 {
+                    let on_ring =
+                        bucket_x.abs_diff(center.0) == ring || bucket_y.abs_diff(center.1) == ring;
+                    if !on_ring {
+                        continue;
+                    }
+                    for index in &self.buckets[bucket_x * BUCKETS + bucket_y] {
+                        found.push((distance_squared(*index), *index));
+                    }
+                }
+            }
+            if found.len() >= count {
+                found.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+                let unsearched = ring as f32 * BUCKET_SIZE;
+                if found[count - 1].0 < unsearched * unsearched {
+                    break;
+                }
+            }
+        }
+        found.sort_unstable_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        found
+            .into_iter()
+            .take(count)
+            .map(|(_, index)| index)
+            .collect()
+    }
+}
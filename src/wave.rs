@@ -0,0 +1,150 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+/// Knobs for how waves are paced and how fast they escalate
+#[derive(Clone, Debug)]
+pub struct WaveConfig {
+    /// seconds before the first wave arrives
+    pub first_breather: f32,
+    /// seconds of calm after a wave has finished spawning
+    pub breather: f32,
+    /// enemies the first wave is allowed to spawn
+    pub first_budget: u32,
+    /// budget multiplier applied every wave
+    pub budget_growth: f32,
+    /// seconds between spawns during the first wave
+    pub spawn_interval: f32,
+    /// spawn interval multiplier applied every wave
+    pub spawn_interval_decay: f32,
+    pub min_spawn_interval: f32,
+    /// extra enemy health per wave after the first, 0.1 is +10% each wave
+    pub health_growth: f32,
+    /// how likely each border is to be picked, ordered left, right, top, bottom
+    pub side_weights: [f32; 4],
+}
+impl Default for WaveConfig {
+    fn default() -> Self {
+        WaveConfig {
+            first_breather: 15.,
+            breather: 20.,
+            first_budget: 5,
+            budget_growth: 1.35,
+            spawn_interval: 3.,
+            spawn_interval_decay: 0.9,
+            min_spawn_interval: 0.5,
+            health_growth: 0.1,
+            side_weights: [1., 1., 1., 1.],
+        }
+    }
+}
+#[derive(Clone, Debug, PartialEq)]
+pub enum WavePhase {
+    /// waiting for the next wave to start
+    Breather { remaining: f32 },
+    /// the current wave still has enemies to send
+    Spawning {
+        remaining_budget: u32,
+        until_next_spawn: f32,
+    },
+}
+/// Decides when enemies are sent in, the spawn itself is still done by the simulation
+#[derive(Clone, Debug)]
+pub struct WaveDirector {
+    pub config: WaveConfig,
+    /// 0 until the first wave starts
+    pub wave: u32,
+    pub phase: WavePhase,
+}
+impl Default for WaveDirector {
+    fn default() -> Self {
+        WaveDirector::new(WaveConfig::default())
+    }
+}
+impl WaveDirector {
+    pub fn new(config: WaveConfig) -> Self {
+        WaveDirector {
+            phase: WavePhase::Breather {
+                remaining: config.first_breather,
+            },
+            wave: 0,
+            config,
+        }
+    }
+    /// Budget of a given wave number starting at 1
+    pub fn budget_for_wave(&self, wave: u32) -> u32 {
+        let growth = self
+            .config
+            .budget_growth
+            .powi(wave.saturating_sub(1) as i32);
+        (self.config.first_budget as f32 * growth).round() as u32
+    }
+    pub fn spawn_interval_for_wave(&self, wave: u32) -> f32 {
+        let decay = self
+            .config
+            .spawn_interval_decay
+            .powi(wave.saturating_sub(1) as i32);
+        (self.config.spawn_interval * decay).max(self.config.min_spawn_interval)
+    }
+    /// Health multiplier for enemies spawned in the current wave
+    pub fn health_multiplier(&self) -> f32 {
+        1. + self.config.health_growth * self.wave.saturating_sub(1) as f32
+    }
+    /// Seconds until the next wave starts, None while a wave is spawning
+    pub fn countdown(&self) -> Option<f32> {
+        match self.phase {
+            WavePhase::Breather { remaining } => Some(remaining.max(0.)),
+            WavePhase::Spawning { .. } => None,
+        }
+    }
+    /// Cuts the current breather short
+    pub fn call_next_wave(&mut self) {
+        if let WavePhase::Breather { remaining } = &mut self.phase {
+            *remaining = 0.;
+        }
+    }
+    fn start_next_wave(&mut self) {
+        self.wave += 1;
+        self.phase = WavePhase::Spawning {
+            remaining_budget: self.budget_for_wave(self.wave),
+            until_next_spawn: 0.,
+        };
+    }
+    /// Advances the wave clock, returns how many enemies should spawn this step
+    pub fn update(&mut self, time_dif: f32) -> u32 {
+        if let WavePhase::Breather { remaining } = &mut self.phase {
+            *remaining -= time_dif;
+            if *remaining > 0. {
+                return 0;
+            }
+            self.start_next_wave();
+        }
+        let spawn_interval = self.spawn_interval_for_wave(self.wave);
+        let mut spawn_count = 0;
+        if let WavePhase::Spawning {
+            remaining_budget,
+            until_next_spawn,
+        } = &mut self.phase
+        {
+            *until_next_spawn -= time_dif;
+            while *until_next_spawn <= 0. && *remaining_budget > 0 {
+                spawn_count += 1;
+                *remaining_budget -= 1;
+                *until_next_spawn += spawn_interval;
+            }
+            if *remaining_budget == 0 {
+                self.phase = WavePhase::Breather {
+                    remaining: self.config.breather,
+                };
+            }
+        }
+        spawn_count
+    }
+    /// Picks which border the next enemy comes from, 0 left, 1 right, 2 top, 3 bottom
+    pub fn pick_side<R: Rng>(&self, rng: &mut R) -> usize {
+        match WeightedIndex::new(self.config.side_weights) {
+            Ok(side_distribution) => side_distribution.sample(rng),
+            // every side weighted to nothing, fall back to an even pick
+            Err(_) => rng.gen_range(0..4),
+        }
+    }
+}
@@ -1,48 +1,158 @@
-use ggez::{
-    glam::Vec2,
-    graphics::{Canvas, Color, DrawParam, Mesh, MeshBuilder},
-    Context,
-};
+use crate::enemy::Enemy;
+
+/// Workers every game starts with
+pub const STARTING_WORKERS: u32 = 2;
+/// Main gun shells spent to hire one more worker
+pub const WORKER_HIRE_COST: u32 = 2;
+/// Where new workers turn up, the middle of the main building
+pub const WORKER_HOME: (i32, i32) = (0, 0);
+
+/// Distance from the middle of the base within which a body counts as near it
+pub const NEAR_BASE_RADIUS: f32 = 150.;
+/// Health a repair puts back into a building
+pub const REPAIR_AMOUNT: f32 = 100.;
+/// What a task is for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TaskKind {
+    /// pick the body with this enemy id up at the first goal and drop it off at the last
+    CollectBody { body_id: u32 },
+    /// patch up a building, the only goal is next to it
+    Repair { building_id: u32 },
+}
+/// How urgent a task is, the job board hands out the lowest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaskPriority {
+    Repair,
+    CollectNearBase,
+    CollectFar,
+}
+/// Errand for a worker, walk to each goal in turn and spend its task time there
 #[derive(Clone)]
 pub struct Task {
-    pub(crate) task_times: Vec<f32>,
-    pub(crate) goals: Vec<(f32, f32)>,
+    /// given by the job board when the task is posted
+    pub id: u32,
+    pub kind: TaskKind,
+    pub priority: TaskPriority,
+    pub task_times: Vec<f32>,
+    pub goals: Vec<(f32, f32)>,
+    /// crafting station the body is carried to, the main gun takes it directly when None
+    pub deposit_at: Option<u32>,
+}
+/// Tasks waiting for a worker
+#[derive(Clone, Default)]
+pub struct JobBoard {
+    pub tasks: Vec<Task>,
+    last_task_id: u32,
+}
+impl JobBoard {
+    /// Puts a task up under the next free id
+    pub fn post(&mut self, mut task: Task) -> u32 {
+        self.last_task_id += 1;
+        task.id = self.last_task_id;
+        self.tasks.push(task);
+        self.last_task_id
+    }
+    /// Takes a task off the board before any worker picks it up
+    pub fn cancel(&mut self, task_id: u32) -> Option<Task> {
+        let task_index = self.tasks.iter().position(|task| task.id == task_id)?;
+        Some(self.tasks.remove(task_index))
+    }
+    /// Takes the most urgent task off the board, the oldest of equally urgent ones
+    pub fn take_next(&mut self) -> Option<Task> {
+        let task_index = self
+            .tasks
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, task)| (task.priority, task.id))
+            .map(|(task_index, _)| task_index)?;
+        Some(self.tasks.remove(task_index))
+    }
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+}
+/// Step of its task a worker is on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorkerState {
+    /// free to take the next task
+    Idle,
+    /// walking its path to the goal with this index
+    Travelling { goal: usize },
+    /// spending the task time at the goal with this index
+    Working { goal: usize, time_left: f32 },
 }
 #[derive(Clone)]
 pub struct Worker {
-    pub(crate) health: f32,
-    pub(crate) position: (i32, i32),
-    pub(crate) speed: u32,
-    pub(crate) path: Vec<(i32, i32)>,
-    pub(crate) task: Task,
-    pub(crate) time_since_path_started: f32,
-    pub(crate) avalible_for_task: bool,
-    pub(crate) ready_for_new_path: bool,
+    /// shown on the roster, never reused within a game
+    pub id: u32,
+    pub health: f32,
+    pub position: (i32, i32),
+    /// grid spaces walked per second
+    pub speed: u32,
+    /// grid space centers in screen space leading to the current goal
+    pub path: Vec<(i32, i32)>,
+    /// grid spaces walked along the path so far
+    pub path_progress: f32,
+    pub task: Option<Task>,
+    pub state: WorkerState,
+    /// body picked up and not delivered yet
+    pub carrying: Option<Enemy>,
 }
 impl Worker {
-    pub fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) {
-        let current_time = ctx.time.time_since_start().as_secs_f32();
-        let time_dif = current_time - self.time_since_path_started;
-        let path_index: usize = (time_dif * self.speed as f32) as usize;
-        if path_index < self.path.len() {
-            self.position = self.path.get(path_index).unwrap().to_owned();
-        } else if self.path.len() as f32 / self.speed as f32 + self.task.task_times[0] > time_dif {
-            //wait the time needed
-        } else {
-            self.ready_for_new_path = true;
+    pub fn new(id: u32, position: (i32, i32)) -> Self {
+        Worker {
+            id,
+            health: 100.,
+            position,
+            speed: 10,
+            path: Vec::new(),
+            path_progress: 0.,
+            task: None,
+            state: WorkerState::Idle,
+            carrying: None,
+        }
+    }
+    pub fn is_available(&self) -> bool {
+        self.state == WorkerState::Idle
+    }
+    /// Sets off along a path towards one of the task's goals
+    pub fn travel_to(&mut self, goal: usize, path: Vec<(i32, i32)>) {
+        self.path = path;
+        self.path_progress = 0.;
+        self.state = WorkerState::Travelling { goal };
+    }
+    /// Drops the task and goes back to being available
+    pub fn finish_task(&mut self) {
+        self.task = None;
+        self.path.clear();
+        self.state = WorkerState::Idle;
+    }
+    /// Walks the path or works at the goal, returns the index of the goal whose work
+    /// finished this step so the simulation can carry out what happens there
+    pub fn update(&mut self, time_dif: f32) -> Option<usize> {
+        match self.state {
+            WorkerState::Idle => None,
+            WorkerState::Travelling { goal } => {
+                self.path_progress += self.speed as f32 * time_dif;
+                let last_index = self.path.len().saturating_sub(1);
+                let path_index = (self.path_progress as usize).min(last_index);
+                if let Some(position) = self.path.get(path_index) {
+                    self.position = *position;
+                }
+                if path_index == last_index {
+                    let time_left = self.task.as_ref().map_or(0., |task| task.task_times[goal]);
+                    self.state = WorkerState::Working { goal, time_left };
+                }
+                None
+            }
+            WorkerState::Working { goal, time_left } => {
+                let time_left = time_left - time_dif;
+                self.state = WorkerState::Working { goal, time_left };
+                (time_left <= 0.).then_some(goal)
+            }
         }
-        let mut mesh_builder = MeshBuilder::new();
-        mesh_builder
-            .circle(
-                ggez::graphics::DrawMode::fill(),
-                [self.position.0 as f32, self.position.1 as f32],
-                5.,
-                0.1,
-                Color::BLUE,
-            )
-            .unwrap();
-        let mesh_data = mesh_builder.build();
-        let mesh = Mesh::from_data(&ctx.gfx, mesh_data);
-        canvas.draw(&mesh, DrawParam::default());
     }
 }
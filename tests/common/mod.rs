@@ -0,0 +1,8 @@
+use the_last_hope::archetype::ArchetypeRegistry;
+use the_last_hope::enemy::Enemy;
+
+/// A grunt, 20 across, standing at a screen space position
+pub fn grunt_at(position: (f32, f32), health: f32) -> Enemy {
+    let archetypes = ArchetypeRegistry::default();
+    Enemy::from_archetype(archetypes.get("grunt").unwrap(), position, health)
+}
@@ -0,0 +1,125 @@
+use the_last_hope::archetype::ArchetypeRegistry;
+use the_last_hope::crafting::{CraftingStation, ShellKind};
+use the_last_hope::enemy::Enemy;
+use the_last_hope::main_gun::MainGun;
+use the_last_hope::map::{Building, BuildingType};
+use the_last_hope::worker::Worker;
+use the_last_hope::{Command, Simulation};
+
+const TICK: f32 = 1. / 60.;
+
+fn crafting_station_at(bottom_left: (usize, usize)) -> Building {
+    let (width, height) = BuildingType::CraftingStation.footprint();
+    Building {
+        bottom_left,
+        width,
+        height,
+        max_health: BuildingType::CraftingStation.max_health(),
+        health: BuildingType::CraftingStation.max_health(),
+        building_type: BuildingType::CraftingStation,
+    }
+}
+
+fn run(station: &mut CraftingStation, main_gun: &mut MainGun, seconds: f32) {
+    for _ in 0..(seconds / TICK).round() as u32 {
+        station.update(TICK, main_gun);
+    }
+}
+
+#[test]
+fn stations_use_up_bodies_by_size_and_hold_shells_until_the_loader_has_room() {
+    let mut station = CraftingStation::new(1, &crafting_station_at((60, 120)));
+    let mut main_gun = MainGun {
+        max_ammo: 2,
+        ..Default::default()
+    };
+    // two swarmlings are not enough for a standard shell, a grunt on top is
+    station.deposit(8.);
+    station.deposit(8.);
+    run(&mut station, &mut main_gun, 1.);
+    assert!(station.crafting.is_none());
+    assert_eq!(station.material, 2);
+    station.deposit(20.);
+    run(&mut station, &mut main_gun, 1.);
+    assert_eq!(station.material, 2);
+    assert!(matches!(station.crafting, Some((ShellKind::Standard, _))));
+    run(&mut station, &mut main_gun, 3.5);
+    assert_eq!(main_gun.magazine, [ShellKind::Standard]);
+
+    // a high explosive shell takes a brute and a grunt, the rest carries over
+    station.recipe = station.recipe.next();
+    station.deposit(32.);
+    station.deposit(20.);
+    run(&mut station, &mut main_gun, TICK);
+    assert_eq!(station.material, 1);
+    assert!(matches!(
+        station.crafting,
+        Some((ShellKind::HighExplosive, _))
+    ));
+    main_gun.max_ammo = 1;
+    run(&mut station, &mut main_gun, 7.5);
+    assert_eq!(station.finished, Some(ShellKind::HighExplosive));
+    assert_eq!(main_gun.ammo(), 1);
+    main_gun.magazine.clear();
+    run(&mut station, &mut main_gun, TICK);
+    assert_eq!(station.finished, None);
+    assert_eq!(main_gun.magazine, [ShellKind::HighExplosive]);
+}
+
+#[test]
+fn workers_carry_bodies_to_the_nearest_crafting_station() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.rooftop_view = false;
+    simulation.main_gun.magazine.clear();
+    let far_station = simulation.add_building(crafting_station_at((20, 60)));
+    let near_station = simulation.add_building(crafting_station_at((60, 120)));
+    let archetypes = ArchetypeRegistry::default();
+    let body = Enemy::from_archetype(archetypes.get("grunt").unwrap(), (-240., -20.), -1.);
+    simulation.enemy_dead_list.push(body);
+    simulation.worker_list.clear();
+    simulation.worker_list.push(Worker::new(1, (-200, -20)));
+    simulation.queue_command(Command::CollectBody { x: -240., y: 20. });
+    simulation.step(TICK);
+    assert_eq!(
+        simulation.worker_list[0].task.as_ref().unwrap().deposit_at,
+        Some(near_station)
+    );
+    for _ in 0..60 * 3 {
+        simulation.step(TICK);
+    }
+    assert!(simulation.enemy_dead_list.is_empty());
+    assert!(simulation.worker_list[0].is_available());
+    assert!(simulation.crafting_hash_map[&near_station]
+        .crafting
+        .is_some());
+    assert!(simulation.crafting_hash_map[&far_station]
+        .crafting
+        .is_none());
+    for _ in 0..60 * 4 {
+        simulation.step(TICK);
+    }
+    assert_eq!(simulation.main_gun.magazine, [ShellKind::Standard]);
+    // the body went through the station, not straight into the gun
+    assert_eq!(simulation.main_gun.ammo_progress, 0.);
+}
+
+#[test]
+fn incendiary_shells_keep_burning_enemies_in_the_blast() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.main_gun.magazine = [ShellKind::Incendiary].into();
+    let archetypes = ArchetypeRegistry::default();
+    let mut enemy = Enemy::from_archetype(archetypes.get("grunt").unwrap(), (0., -100.), 500.);
+    enemy.speed = 0;
+    simulation.enemy_alive_list.push(enemy);
+    simulation.queue_command(Command::Fire { x: 0., y: 100. });
+    while simulation.main_gun.explosion_info_list.is_empty() {
+        simulation.step(TICK);
+    }
+    // half the damage of a standard shell on impact
+    assert_eq!(simulation.enemy_alive_list[0].health, 450.);
+    for _ in 0..60 * 8 {
+        simulation.step(TICK);
+    }
+    let burn_damage = ShellKind::Incendiary.burn_damage() * 4.;
+    assert!((simulation.enemy_alive_list[0].health - (450. - burn_damage)).abs() < 0.1);
+}
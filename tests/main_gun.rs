@@ -0,0 +1,119 @@
+mod common;
+
+use common::grunt_at;
+use the_last_hope::archetype::ArchetypeRegistry;
+use the_last_hope::enemy::Enemy;
+use the_last_hope::main_gun::{DamageFalloff, MainGun};
+use the_last_hope::spatial::SpatialIndex;
+use the_last_hope::{Command, Simulation};
+
+const TICK: f32 = 1. / 60.;
+
+fn main_gun(damage_falloff: DamageFalloff) -> MainGun {
+    MainGun {
+        damage_falloff,
+        edge_damage: 0.25,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn falloff_curves_run_from_full_damage_to_the_edge_damage() {
+    assert_eq!(DamageFalloff::Flat.multiplier(0., 0.25), 1.);
+    assert_eq!(DamageFalloff::Flat.multiplier(1., 0.25), 1.);
+    assert_eq!(DamageFalloff::Linear.multiplier(0., 0.25), 1.);
+    assert_eq!(DamageFalloff::Linear.multiplier(0.5, 0.25), 0.625);
+    assert_eq!(DamageFalloff::Linear.multiplier(1., 0.25), 0.25);
+    assert_eq!(DamageFalloff::Quadratic.multiplier(0.5, 0.25), 0.8125);
+    assert_eq!(DamageFalloff::Quadratic.multiplier(1., 0.25), 0.25);
+    // distances past the edge do not go below the edge damage
+    assert_eq!(DamageFalloff::Linear.multiplier(2., 0.), 0.);
+}
+
+#[test]
+fn blasts_reach_the_nearest_part_of_an_enemy() {
+    let main_gun = main_gun(DamageFalloff::Linear);
+    // world y points up while enemy positions are in screen space
+    let center = (0., 100.);
+    let under_the_shell = grunt_at((0., -100.), 500.);
+    assert_eq!(
+        main_gun.blast_damage(center, 50., 100., &under_the_shell),
+        100.
+    );
+    // a triangle covering ground zero takes the full blast
+    let overlapping = grunt_at((4., -100.), 500.);
+    assert_eq!(main_gun.blast_damage(center, 50., 100., &overlapping), 100.);
+    // half way out from the base of the triangle, 20 behind a grunt's position
+    let half_way = grunt_at((0., -55.), 500.);
+    assert!((half_way.distance_to_body((0., -100.)) - 25.).abs() < 0.001);
+    assert!((main_gun.blast_damage(center, 50., 100., &half_way) - 62.5).abs() < 0.01);
+    // the center is outside the radius but the base still reaches in
+    let rim = grunt_at((0., -35.), 500.);
+    assert!(MainGun::in_blast(center, 50., &rim));
+    assert!((main_gun.blast_damage(center, 50., 100., &rim) - 32.5).abs() < 0.01);
+    let clear = grunt_at((0., -29.), 500.);
+    assert!(!MainGun::in_blast(center, 50., &clear));
+    assert_eq!(main_gun.blast_damage(center, 50., 100., &clear), 0.);
+    // past the diagonal of the box check but inside the box
+    let corner = grunt_at((60., -160.), 500.);
+    assert!(!MainGun::in_blast(center, 50., &corner));
+}
+
+#[test]
+fn blasts_miss_enemies_whose_triangle_stays_outside() {
+    let main_gun = main_gun(DamageFalloff::Linear);
+    // beside the blast the circle around the corners reaches 42.6 out, the slanted side only 58.2
+    let beside = grunt_at((-65., -100.), 500.);
+    assert!(65. - beside.body_radius() < 50.);
+    assert!((beside.distance_to_body((0., -100.)) - 58.21).abs() < 0.01);
+    assert!(!MainGun::in_blast((0., 100.), 50., &beside));
+    assert_eq!(main_gun.blast_damage((0., 100.), 50., 100., &beside), 0.);
+    // turning it side on brings its base within reach
+    let mut turned = beside.clone();
+    turned.rotation = std::f32::consts::FRAC_PI_2;
+    assert!((turned.distance_to_body((0., -100.)) - 45.).abs() < 0.001);
+    assert!(MainGun::in_blast((0., 100.), 50., &turned));
+}
+
+#[test]
+fn wide_enemies_are_caught_by_their_base_corners() {
+    let archetypes = ArchetypeRegistry::default();
+    let brute = Enemy::from_archetype(archetypes.get("brute").unwrap(), (-55.6, -38.), 500.);
+    // 83 out is past the blast plus the brute's size of 32, its corner is 42.4 away
+    assert!(brute.size + 50. < 83.);
+    assert!((brute.distance_to_body((0., -100.)) - 42.43).abs() < 0.01);
+    assert!(MainGun::in_blast((0., 100.), 50., &brute));
+    let spatial_index = SpatialIndex::new(std::slice::from_ref(&brute));
+    assert!((spatial_index.largest_body_radius - brute.body_radius()).abs() < 0.001);
+}
+
+#[test]
+fn enemies_below_and_above_the_blast_are_missed() {
+    let main_gun = main_gun(DamageFalloff::Flat);
+    for screen_y in [-300., 100.] {
+        let enemy = grunt_at((0., screen_y), 500.);
+        assert_eq!(main_gun.blast_damage((0., 100.), 50., 100., &enemy), 0.);
+    }
+}
+
+#[test]
+fn enemies_at_the_edge_of_a_shell_take_less_than_ground_zero() {
+    let mut simulation = Simulation::with_seed(1);
+    for position in [(0., -100.), (-50., -100.)] {
+        let mut enemy = grunt_at(position, 500.);
+        enemy.speed = 0;
+        simulation.enemy_alive_list.push(enemy);
+    }
+    simulation.queue_command(Command::Fire { x: 0., y: 100. });
+    while simulation.main_gun.explosion_info_list.is_empty() {
+        simulation.step(TICK);
+    }
+    let health: Vec<f32> = simulation
+        .enemy_alive_list
+        .iter()
+        .map(|enemy| enemy.health)
+        .collect();
+    assert_eq!(health[0], 400.);
+    // turned towards the base while the shell flew, its tip is 38.5 out
+    assert!((health[1] - 457.82).abs() < 0.01);
+}
@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+use the_last_hope::map::{Building, BuildingType, Map, Terrain, BARRICADE_COST};
+
+fn building(
+    building_type: BuildingType,
+    bottom_left: (usize, usize),
+    width: usize,
+    height: usize,
+) -> Building {
+    Building {
+        building_type,
+        bottom_left,
+        width,
+        height,
+        max_health: 100.,
+        health: 100.,
+    }
+}
+
+fn sentry(bottom_left: (usize, usize), width: usize, height: usize) -> Building {
+    building(BuildingType::Sentry, bottom_left, width, height)
+}
+
+fn barricade(bottom_left: (usize, usize), width: usize, height: usize) -> Building {
+    building(BuildingType::Baricade, bottom_left, width, height)
+}
+
+/// Grid spaces an enemy walks through from a start until it reaches a building
+fn route(map: &Map, mut location: (usize, usize)) -> Vec<(usize, usize)> {
+    let mut route = vec![location];
+    while let Some(direction) = map.map[location.0][location.1].direction.clone() {
+        let heading = direction.heading();
+        location = (
+            (location.0 as f32 + heading.0.round()) as usize,
+            (location.1 as f32 + heading.1.round()) as usize,
+        );
+        route.push(location);
+    }
+    route
+}
+
+fn distances(map: &Map) -> Vec<Vec<u32>> {
+    map.map
+        .iter()
+        .map(|column| column.iter().map(|space| space.distance).collect())
+        .collect()
+}
+
+fn rebuilt(buildings: &[(u32, Building)]) -> Map {
+    let building_hash_map: HashMap<u32, Building> = buildings.iter().cloned().collect();
+    Map::new(&building_hash_map)
+}
+
+#[test]
+fn incremental_updates_match_a_full_rebuild() {
+    let main_building = (0, sentry((116, 131), 18, 12));
+    let wall = (1, sentry((60, 140), 40, 4));
+    let mut map = rebuilt(std::slice::from_ref(&main_building));
+
+    map.add_building(wall.0, &wall.1);
+    assert_eq!(
+        distances(&map),
+        distances(&rebuilt(&[main_building.clone(), wall.clone()]))
+    );
+
+    let bigger_wall = sentry((60, 140), 40, 20);
+    map.resize_building(wall.0, &wall.1, &bigger_wall);
+    assert_eq!(
+        distances(&map),
+        distances(&rebuilt(&[main_building.clone(), (1, bigger_wall.clone())]))
+    );
+
+    map.remove_building(&bigger_wall);
+    assert_eq!(distances(&map), distances(&rebuilt(&[main_building])));
+}
+
+#[test]
+fn directions_lead_into_a_newly_placed_building() {
+    let mut map = rebuilt(&[(0, sentry((116, 131), 18, 12))]);
+    let far_corner = map.map[0][0].distance;
+    map.add_building(1, &sentry((2, 6), 4, 4));
+    assert!(map.map[0][0].distance < far_corner);
+    assert_eq!(map.map[1][2].distance, Terrain::Ground.cost());
+    assert!(map.map[1][2].direction.is_some());
+}
+
+#[test]
+fn enemies_take_the_cheapest_route_not_the_shortest() {
+    let mut map = rebuilt(&[(0, sentry((116, 131), 18, 12))]);
+    let start = (100, 125);
+    let straight = map.map[start.0][start.1].distance;
+    assert_eq!(straight, 16 * Terrain::Ground.cost());
+
+    map.add_kill_zone((108, 125), 3, 1000);
+    let around = map.map[start.0][start.1].distance;
+    assert!(around > straight && around < straight + 1000);
+    assert!(route(&map, start)
+        .iter()
+        .all(|(x, y)| map.map[*x][*y].threat == 0));
+    map.remove_kill_zone((108, 125), 3, 1000);
+    assert_eq!(map.map[start.0][start.1].distance, straight);
+
+    map.set_terrain((102, 128), 8, 6, Terrain::Mud);
+    assert!(map.map[start.0][start.1].distance > straight);
+    assert!(route(&map, start)
+        .iter()
+        .all(|(x, y)| map.map[*x][*y].terrain == Terrain::Ground));
+}
+
+#[test]
+fn barricades_are_only_broken_through_when_going_around_costs_more() {
+    let mut map = rebuilt(&[(0, sentry((116, 131), 18, 12))]);
+    let start = (100, 125);
+    map.add_building(1, &barricade((108, 128), 2, 6));
+    let route_around = route(&map, start);
+    assert!(route_around
+        .iter()
+        .all(|(x, y)| map.map[*x][*y].building.is_none() || map.map[*x][*y].is_goal()));
+
+    // a ring of barricades leaves no way around
+    map.add_building(2, &barricade((110, 137), 30, 2));
+    map.add_building(3, &barricade((110, 115), 30, 2));
+    map.add_building(4, &barricade((110, 135), 2, 20));
+    map.add_building(5, &barricade((138, 135), 2, 20));
+    assert!(map.map[start.0][start.1].distance > BARRICADE_COST);
+    assert!(route(&map, start)
+        .iter()
+        .any(|(x, y)| !map.map[*x][*y].is_goal() && map.map[*x][*y].building.is_some()));
+}
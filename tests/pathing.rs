@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use the_last_hope::map::{Building, BuildingType, Map};
+use the_last_hope::pathing::{build_path, grid_to_screen_position};
+
+fn wall(bottom_left: (usize, usize), width: usize, height: usize) -> Building {
+    Building {
+        building_type: BuildingType::Baricade,
+        bottom_left,
+        width,
+        height,
+        max_health: 500.,
+        health: 500.,
+    }
+}
+
+fn grid_of(position: (i32, i32)) -> (usize, usize) {
+    Map::convert_position_to_grid_position((position.0 as f32, position.1 as f32))
+}
+
+fn assert_walkable(map: &Map, path: &[(i32, i32)]) {
+    for step in path.windows(2) {
+        let (from, to) = (grid_of(step[0]), grid_of(step[1]));
+        assert!(from.0.abs_diff(to.0) <= 1 && from.1.abs_diff(to.1) <= 1);
+    }
+    for position in &path[1..] {
+        let grid_position = grid_of(*position);
+        assert!(map.map[grid_position.0][grid_position.1].building.is_none());
+    }
+}
+
+#[test]
+fn workers_walk_around_buildings_in_their_way() {
+    // a wall from column 100 to 110 across rows 80 to 160
+    let buildings = HashMap::from([(1, wall((100, 160), 10, 80))]);
+    let map = Map::new(&buildings);
+    let start = grid_to_screen_position((90, 120));
+    let goal = grid_to_screen_position((120, 120));
+    let path = build_path(
+        &map,
+        (start.0 as f32, start.1 as f32),
+        (goal.0 as f32, goal.1 as f32),
+    )
+    .unwrap();
+    assert_eq!(path[0], start);
+    assert_eq!(*path.last().unwrap(), goal);
+    assert_walkable(&map, &path);
+    // it has to go over or under the wall, far longer than the straight 30 spaces
+    assert!(path.len() > 60);
+    assert!(path
+        .iter()
+        .any(|position| !(80..160).contains(&grid_of(*position).1)));
+}
+
+#[test]
+fn targets_inside_a_building_stop_next_to_it() {
+    let buildings = HashMap::from([(1, wall((100, 130), 10, 10))]);
+    let map = Map::new(&buildings);
+    let start = grid_to_screen_position((60, 125));
+    // the middle of the building's left half
+    let goal = grid_to_screen_position((102, 125));
+    let path = build_path(
+        &map,
+        (start.0 as f32, start.1 as f32),
+        (goal.0 as f32, goal.1 as f32),
+    )
+    .unwrap();
+    assert_walkable(&map, &path);
+    assert_eq!(grid_of(*path.last().unwrap()), (99, 125));
+}
+
+#[test]
+fn workers_leave_the_building_they_start_in() {
+    let buildings = HashMap::from([(1, wall((100, 130), 10, 10))]);
+    let map = Map::new(&buildings);
+    let start = grid_to_screen_position((105, 125));
+    let goal = grid_to_screen_position((105, 140));
+    let path = build_path(
+        &map,
+        (start.0 as f32, start.1 as f32),
+        (goal.0 as f32, goal.1 as f32),
+    )
+    .unwrap();
+    assert_eq!(path[0], start);
+    assert_eq!(*path.last().unwrap(), goal);
+    // straight down through its own walls
+    assert_eq!(path.len(), 16);
+}
+
+#[test]
+fn closed_off_targets_have_no_path() {
+    // a ring of walls around rows 110 to 120 and columns 110 to 120
+    let buildings = HashMap::from([
+        (1, wall((108, 110), 14, 2)),
+        (2, wall((108, 122), 14, 2)),
+        (3, wall((108, 120), 2, 10)),
+        (4, wall((120, 120), 2, 10)),
+    ]);
+    let map = Map::new(&buildings);
+    let start = grid_to_screen_position((60, 60));
+    let goal = grid_to_screen_position((115, 115));
+    assert!(build_path(
+        &map,
+        (start.0 as f32, start.1 as f32),
+        (goal.0 as f32, goal.1 as f32),
+    )
+    .is_none());
+}
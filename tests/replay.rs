@@ -0,0 +1,107 @@
+use the_last_hope::map::BuildingType;
+use the_last_hope::replay::{Replay, ReplayPlayer};
+use the_last_hope::{Command, Simulation};
+
+const TICK: f32 = 1. / 60.;
+
+fn enemy_positions(simulation: &Simulation) -> Vec<(f32, f32)> {
+    simulation
+        .enemy_alive_list
+        .iter()
+        .chain(simulation.enemy_dead_list.iter())
+        .map(|enemy| enemy.position)
+        .collect()
+}
+
+#[test]
+fn replay_survives_the_text_format() {
+    let mut replay = Replay::new(7);
+    replay.record(3, Command::Fire { x: 10.5, y: -0.1 });
+    replay.record(3, Command::SpawnEnemy);
+    replay.record(4, Command::FireAtEnemy { enemy_id: 1 });
+    replay.record(5, Command::PrioritizeTarget { index: 1 });
+    replay.record(6, Command::RemoveTarget { index: 0 });
+    replay.record(7, Command::ClearTargets);
+    replay.record(90, Command::CollectBody { x: -3., y: 1e-3 });
+    replay.record(91, Command::SwitchPerspective);
+    replay.record(92, Command::CallNextWave);
+    replay.record(
+        95,
+        Command::PlaceBuilding {
+            building_type: BuildingType::Baricade,
+            x: 40,
+            y: -3,
+            rotated: true,
+        },
+    );
+    replay.record(
+        96,
+        Command::PlaceBuilding {
+            building_type: BuildingType::CraftingStation,
+            x: 20,
+            y: 60,
+            rotated: false,
+        },
+    );
+    replay.record(97, Command::CycleRecipe { building_id: 2 });
+    replay.record(98, Command::HireWorker);
+    replay.record(99, Command::RepairBuilding { building_id: 0 });
+    replay.record(99, Command::CancelTask { task_id: 4 });
+    replay.record(100, Command::ToggleAutoCollect);
+    assert_eq!(Replay::from_text(&replay.to_text()).unwrap(), replay);
+    assert!(Replay::from_text("seed 7\n3 teleport\n").is_err());
+}
+
+#[test]
+fn ids_and_indices_must_be_whole_numbers() {
+    let replay = Replay::from_text("seed 1\n5 lockon 16777217\n6 untarget 3\n").unwrap();
+    assert_eq!(
+        replay.commands,
+        [
+            (
+                5,
+                Command::FireAtEnemy {
+                    enemy_id: 16_777_217
+                }
+            ),
+            (6, Command::RemoveTarget { index: 3 }),
+        ]
+    );
+    for bad_line in [
+        "5 lockon 1.7",
+        "5 cancel -3",
+        "5 untarget 2.0",
+        "5 place sentry 4.5 10 0",
+        "5 place sentry 4 10 2",
+    ] {
+        assert!(Replay::from_text(&format!("seed 1\n{}\n", bad_line)).is_err());
+    }
+}
+
+#[test]
+fn replaying_recorded_commands_rebuilds_the_game() {
+    let mut recorded = Simulation::with_seed(99);
+    for tick in 0..600 {
+        if tick % 50 == 0 {
+            recorded.queue_command(Command::SpawnEnemy);
+        }
+        if tick == 200 {
+            recorded.queue_command(Command::Fire { x: 120., y: -40. });
+        }
+        if tick == 300 {
+            recorded.queue_command(Command::FireAtEnemy { enemy_id: 2 });
+        }
+        recorded.step(TICK);
+    }
+
+    let replay = Replay::from_text(&recorded.replay.to_text()).unwrap();
+    let mut replay_player = ReplayPlayer::new(replay);
+    let mut replayed = replay_player.new_simulation();
+    for _ in 0..600 {
+        replay_player.queue_commands(&mut replayed);
+        replayed.step(TICK);
+    }
+    assert!(replay_player.is_finished());
+    assert_eq!(enemy_positions(&recorded), enemy_positions(&replayed));
+    assert_eq!(recorded.replay, replayed.replay);
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::grunt_at;
+use the_last_hope::enemy::Enemy;
+use the_last_hope::map::BuildingType;
+use the_last_hope::sentry::TargetPriority;
+use the_last_hope::spatial::SpatialIndex;
+use the_last_hope::{Command, Simulation};
+
+const TICK: f32 = 1. / 60.;
+
+fn enemy_at(position: (f32, f32), health: f32) -> Enemy {
+    let mut enemy = grunt_at(position, health);
+    // keep them standing still so only the sentry changes anything
+    enemy.speed = 0;
+    enemy
+}
+
+/// A simulation with a single sentry far away from the base, returning its id and center
+fn simulation_with_sentry() -> (Simulation, u32, (f32, f32)) {
+    let mut simulation = Simulation::with_seed(4);
+    let sentry_id = simulation
+        .place_building(BuildingType::Sentry, (40, 44), false)
+        .unwrap();
+    let center = simulation.sentry_hash_map[&sentry_id].position;
+    (simulation, sentry_id, center)
+}
+
+#[test]
+fn sentries_pick_targets_by_priority() {
+    let (mut simulation, sentry_id, center) = simulation_with_sentry();
+    simulation.enemy_alive_list = vec![
+        enemy_at((center.0 + 20., center.1), 90.),
+        enemy_at((center.0 - 40., center.1), 30.),
+        enemy_at((center.0 + 200., center.1), 5.),
+    ];
+    let sentry = &simulation.sentry_hash_map[&sentry_id];
+    assert_eq!(
+        sentry.pick_target(
+            &simulation.enemy_alive_list,
+            &SpatialIndex::new(&simulation.enemy_alive_list),
+            &simulation.map,
+        ),
+        Some(0)
+    );
+    simulation.queue_command(Command::CycleSentryPriority {
+        building_id: sentry_id,
+    });
+    simulation.step(TICK);
+    let sentry = &simulation.sentry_hash_map[&sentry_id];
+    assert_eq!(sentry.priority, TargetPriority::Weakest);
+    // the weakest one overall is out of range
+    assert_eq!(
+        sentry.pick_target(
+            &simulation.enemy_alive_list,
+            &SpatialIndex::new(&simulation.enemy_alive_list),
+            &simulation.map,
+        ),
+        Some(1)
+    );
+}
+
+#[test]
+fn sentries_turn_towards_and_kill_enemies_in_range() {
+    let (mut simulation, sentry_id, center) = simulation_with_sentry();
+    // straight up the screen, half a turn from where the barrel starts
+    simulation
+        .enemy_alive_list
+        .push(enemy_at((center.0, center.1 - 30.), 40.));
+    simulation
+        .enemy_alive_list
+        .push(enemy_at((center.0 + 300., center.1), 40.));
+    let turn_speed = simulation.sentry_hash_map[&sentry_id].turn_speed;
+    let mut last_rotation = simulation.sentry_hash_map[&sentry_id].rotation;
+    for _ in 0..60 * 3 {
+        simulation.step(TICK);
+        let rotation = simulation.sentry_hash_map[&sentry_id].rotation;
+        let turn = (rotation - last_rotation).abs();
+        assert!(turn.min(std::f32::consts::TAU - turn) <= turn_speed * TICK + 0.001);
+        last_rotation = rotation;
+    }
+    assert_eq!(simulation.enemy_dead_list.len(), 1);
+    assert_eq!(simulation.enemy_alive_list.len(), 1);
+    assert_eq!(simulation.enemy_alive_list[0].health, 40.);
+    assert_eq!(simulation.sentry_hash_map[&sentry_id].fired_count, 3);
+
+    simulation.remove_building(sentry_id);
+    assert!(simulation.sentry_hash_map.is_empty());
+}
@@ -0,0 +1,579 @@
+mod common;
+
+use common::grunt_at;
+use std::f32::consts::PI;
+use the_last_hope::archetype::ArchetypeRegistry;
+use the_last_hope::crafting::ShellKind;
+use the_last_hope::enemy::Enemy;
+use the_last_hope::map::{Building, BuildingType, PlacementError, Terrain};
+use the_last_hope::worker::{Worker, WorkerState, STARTING_WORKERS, WORKER_HIRE_COST};
+use the_last_hope::{Command, LossConditions, LossReason, Simulation};
+
+const TICK: f32 = 1. / 60.;
+
+#[test]
+fn enemies_attack_the_base_until_they_are_killed() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.enemy_alive_list.push(grunt_at((-100., 0.), 80.));
+    for _ in 0..60 * 10 {
+        simulation.step(TICK);
+    }
+    let enemy = &simulation.enemy_alive_list[0];
+    assert_eq!(enemy.building_hit, Some(0));
+    let attack_damage = enemy.attack_damage;
+    let main_building = &simulation.building_hash_map[&0];
+    let health_before_kill = main_building.health;
+    assert!(health_before_kill < main_building.max_health);
+    assert!(health_before_kill > main_building.max_health - attack_damage * 10.);
+
+    // one more second of chipping away, then it dies at the wall and leaves a corpse
+    for _ in 0..60 {
+        simulation.step(TICK);
+    }
+    let health_at_kill = simulation.building_hash_map[&0].health;
+    assert!((health_before_kill - health_at_kill - attack_damage).abs() < 0.01);
+    simulation.enemy_alive_list[0].health = -1.;
+    for _ in 0..60 {
+        simulation.step(TICK);
+    }
+    assert!(simulation.enemy_alive_list.is_empty());
+    assert_eq!(simulation.enemy_dead_list.len(), 1);
+    assert_eq!(simulation.building_hash_map[&0].health, health_at_kill);
+    assert!(!simulation.is_over());
+}
+
+#[test]
+fn shell_kills_enemy_inside_the_blast() {
+    let mut simulation = Simulation::with_seed(1);
+    // world y points up while enemy positions are in screen space
+    simulation.enemy_alive_list.push(grunt_at((0., -100.), 50.));
+    simulation.queue_command(Command::Fire { x: 0., y: 100. });
+    for _ in 0..60 * 3 {
+        simulation.step(TICK);
+    }
+    assert_eq!(simulation.main_gun.fired_count, 1);
+    assert!(simulation.enemy_alive_list.is_empty());
+    assert_eq!(simulation.enemy_dead_list.len(), 1);
+}
+
+#[test]
+fn shells_fly_to_the_target_and_miss_enemies_that_walked_off() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.main_gun.muzzle_velocity = 100.;
+    let mut enemy = grunt_at((0., -150.), 500.);
+    enemy.speed = 0;
+    simulation.enemy_alive_list.push(enemy);
+    simulation.queue_command(Command::Fire { x: 0., y: 150. });
+    while simulation.main_gun.fired_count == 0 {
+        simulation.step(TICK);
+    }
+    let fired_time = simulation.main_gun.last_fired;
+    assert_eq!(simulation.main_gun.shells_in_flight[0].flight_time, 1.5);
+    assert!(simulation.main_gun.explosion_info_list.is_empty());
+    // the enemy steps out of the blast before the shell comes down
+    simulation.enemy_alive_list[0].position = (0., -300.);
+    while simulation.main_gun.explosion_info_list.is_empty() {
+        simulation.step(TICK);
+    }
+    assert!(simulation.main_gun.shells_in_flight.is_empty());
+    let started_time = simulation.main_gun.explosion_info_list[0].started_time;
+    assert!((started_time - fired_time - 1.5).abs() < TICK * 1.5);
+    assert_eq!(simulation.enemy_alive_list[0].health, 500.);
+}
+
+/// Health of a runner walking in on the base after one shell aimed by the given command lands
+fn health_after_shot_at_runner(command: Command) -> f32 {
+    let mut simulation = Simulation::with_seed(1);
+    let archetypes = ArchetypeRegistry::default();
+    let mut runner = Enemy::from_archetype(archetypes.get("runner").unwrap(), (-250., -150.), 500.);
+    runner.id = 1;
+    simulation.enemy_alive_list.push(runner);
+    simulation.queue_command(command);
+    while simulation.main_gun.explosion_info_list.is_empty() {
+        simulation.step(TICK);
+    }
+    simulation.enemy_alive_list[0].health
+}
+
+#[test]
+fn locked_on_shells_lead_moving_enemies() {
+    // by the time the gun turns and the shell flies the runner is long gone from where it was
+    assert_eq!(
+        health_after_shot_at_runner(Command::Fire { x: -250., y: 150. }),
+        500.
+    );
+    assert_eq!(
+        health_after_shot_at_runner(Command::FireAtEnemy { enemy_id: 1 }),
+        400.
+    );
+}
+
+#[test]
+fn locked_targets_are_dropped_when_their_enemy_dies() {
+    let mut simulation = Simulation::with_seed(1);
+    let mut enemy = grunt_at((-250., -150.), 500.);
+    enemy.id = 7;
+    simulation.enemy_alive_list.push(enemy);
+    simulation.queue_command(Command::FireAtEnemy { enemy_id: 7 });
+    simulation.queue_command(Command::FireAtEnemy { enemy_id: 8 });
+    simulation.step(TICK);
+    assert_eq!(simulation.main_gun.target_info_list.len(), 1);
+    assert_eq!(simulation.enemy_at((-250., -150.)), Some(7));
+    simulation.enemy_alive_list[0].health = -1.;
+    simulation.step(TICK);
+    simulation.step(TICK);
+    assert!(simulation.main_gun.target_info_list.is_empty());
+    assert_eq!(simulation.main_gun.ammo(), 8);
+}
+
+#[test]
+fn queued_targets_can_be_picked_reordered_and_taken_back() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.main_gun.max_targets = 3;
+    simulation.main_gun.magazine = [ShellKind::Standard, ShellKind::HighExplosive].into();
+    for x in [100., 200., 300., 400.] {
+        simulation.queue_command(Command::Fire { x, y: 100. });
+    }
+    simulation.step(TICK);
+    let target_xs = |simulation: &Simulation| -> Vec<f32> {
+        simulation
+            .main_gun
+            .target_info_list
+            .iter()
+            .map(|target| target.x)
+            .collect()
+    };
+    // the queue is full after three, the last click is ignored
+    assert_eq!(target_xs(&simulation), [100., 200., 300.]);
+    assert_eq!(simulation.main_gun.target_at(203., 96.), Some(1));
+    assert_eq!(simulation.main_gun.target_at(250., 100.), None);
+    // the second target gets the high explosive shell
+    assert_eq!(simulation.main_gun.predicted_blast_radius(1), 70.);
+    assert_eq!(simulation.main_gun.predicted_blast_radius(2), 50.);
+
+    simulation.queue_command(Command::PrioritizeTarget { index: 2 });
+    simulation.queue_command(Command::RemoveTarget { index: 1 });
+    simulation.queue_command(Command::RemoveTarget { index: 5 });
+    simulation.step(TICK);
+    assert_eq!(target_xs(&simulation), [300., 200.]);
+    simulation.queue_command(Command::ClearTargets);
+    for _ in 0..60 * 10 {
+        simulation.step(TICK);
+    }
+    assert_eq!(simulation.main_gun.fired_count, 0);
+    assert_eq!(simulation.main_gun.ammo(), 2);
+}
+
+#[test]
+fn fast_enemies_stay_on_the_map_along_the_border() {
+    let mut simulation = Simulation::with_seed(1);
+    let mut archetype = ArchetypeRegistry::default().get("runner").unwrap().clone();
+    archetype.speed = 60;
+    // facing off the map on the right and bottom borders and into the corner
+    for (position, heading) in [
+        ((500., 0.), (1., 0.)),
+        ((0., 500.), (0., 1.)),
+        ((500., 500.), (1., 1.)),
+    ] {
+        let mut enemy = Enemy::from_archetype(&archetype, position, 50.);
+        enemy.rotation = Enemy::rotation_of(heading);
+        simulation.enemy_alive_list.push(enemy);
+    }
+    for _ in 0..60 * 3 {
+        simulation.step(TICK);
+    }
+    for enemy in &simulation.enemy_alive_list {
+        assert!((-500.0..=500.).contains(&enemy.position.0));
+        assert!((-500.0..=500.).contains(&enemy.position.1));
+    }
+}
+
+fn enemies_after_spawning(seed: u64) -> Vec<(f32, f32, f32, f32)> {
+    let mut simulation = Simulation::with_seed(seed);
+    for _ in 0..5 {
+        simulation.queue_command(Command::SpawnEnemy);
+        simulation.step(TICK);
+    }
+    simulation
+        .enemy_alive_list
+        .iter()
+        .map(|enemy| (enemy.health, enemy.size, enemy.position.0, enemy.position.1))
+        .collect()
+}
+
+#[test]
+fn same_seed_spawns_the_same_enemies() {
+    assert_eq!(enemies_after_spawning(42), enemies_after_spawning(42));
+    assert_ne!(enemies_after_spawning(42), enemies_after_spawning(43));
+}
+
+#[test]
+fn waves_spawn_their_budget_after_the_breather() {
+    let mut simulation = Simulation::with_seed(5);
+    let first_breather = simulation.wave_director.config.first_breather;
+    let first_budget = simulation.wave_director.budget_for_wave(1) as usize;
+    let mut elapsed = 0.;
+    while elapsed < first_breather - 1. {
+        simulation.step(TICK);
+        elapsed += TICK;
+    }
+    assert!(simulation.enemy_alive_list.is_empty());
+    assert_eq!(simulation.wave_director.wave, 0);
+
+    simulation.queue_command(Command::CallNextWave);
+    simulation.step(TICK);
+    assert_eq!(simulation.wave_director.wave, 1);
+    assert_eq!(simulation.enemy_alive_list.len(), 1);
+    for _ in 0..60 * 20 {
+        simulation.step(TICK);
+    }
+    assert_eq!(simulation.enemy_alive_list.len(), first_budget);
+    assert!(simulation.wave_director.countdown().is_some());
+    assert!(
+        simulation.wave_director.budget_for_wave(2) > simulation.wave_director.budget_for_wave(1)
+    );
+}
+
+#[test]
+fn spawned_enemies_take_their_stats_from_the_archetype_file() {
+    let mut simulation = Simulation::with_seed(9);
+    simulation.archetypes = ArchetypeRegistry::from_toml(
+        r#"
+        [[archetype]]
+        name = "tank"
+        speed = 4
+        health = [200, 200]
+        size = 40
+        color = [0, 0, 255]
+        corpse_value = 9
+        attack_damage = 25
+        "#,
+    )
+    .unwrap();
+    simulation.queue_command(Command::SpawnEnemy);
+    simulation.step(TICK);
+    let enemy = &simulation.enemy_alive_list[0];
+    assert_eq!(enemy.kind, "tank");
+    assert_eq!(enemy.health, 200.);
+    assert_eq!(enemy.size, 40.);
+    assert_eq!(enemy.width, 0.5);
+    assert_eq!(enemy.speed, 4);
+    assert_eq!(enemy.corpse_value, 9);
+    assert_eq!(enemy.attack_damage, 25.);
+    assert!(ArchetypeRegistry::from_toml("").is_err());
+}
+
+#[test]
+fn enemies_turn_smoothly_and_walk_diagonals() {
+    let mut simulation = Simulation::with_seed(3);
+    simulation
+        .enemy_alive_list
+        .push(grunt_at((-300., -300.), 80.));
+    let mut rotations = vec![simulation.enemy_alive_list[0].rotation];
+    for _ in 0..60 * 5 {
+        simulation.step(TICK);
+        rotations.push(simulation.enemy_alive_list[0].rotation);
+    }
+    let max_turn = 3. * PI * TICK + 0.001;
+    for pair in rotations.windows(2) {
+        let turn = (pair[1] - pair[0] + PI).rem_euclid(2. * PI) - PI;
+        assert!(turn.abs() <= max_turn);
+    }
+    // heading towards the base from the top left corner is diagonal
+    let heading = rotations.last().unwrap() % (PI / 2.);
+    assert!(heading > 0.1 && heading < PI / 2. - 0.1);
+}
+
+#[test]
+fn buildings_are_checked_before_they_are_placed() {
+    let mut simulation = Simulation::with_seed(1);
+    // right next to the main building's left wall
+    let first = simulation.place_building(BuildingType::Sentry, (100, 131), false);
+    assert_eq!(first, Ok(1));
+    assert_eq!(
+        simulation.place_building(BuildingType::Sentry, (102, 129), false),
+        Err(PlacementError::Overlap)
+    );
+    assert_eq!(
+        simulation.place_building(BuildingType::Baricade, (248, 10), false),
+        Err(PlacementError::OutOfBounds)
+    );
+    assert_eq!(
+        simulation.place_building(BuildingType::Baricade, (-2, 10), false),
+        Err(PlacementError::OutOfBounds)
+    );
+
+    // wall the base in with barricades, the piece closing the last gap is refused
+    let mut last_result = Ok(0);
+    for x in (110..140).step_by(6) {
+        last_result = simulation.place_building(BuildingType::Baricade, (x, 115), false);
+        assert!(last_result.is_ok());
+        last_result = simulation.place_building(BuildingType::Baricade, (x, 135), false);
+        assert!(last_result.is_ok());
+    }
+    assert_eq!(last_result, Ok(11));
+    for y in (121..=133).step_by(6) {
+        assert!(simulation
+            .place_building(BuildingType::Baricade, (140, y), true)
+            .is_ok());
+    }
+    assert!(simulation
+        .place_building(BuildingType::Baricade, (108, 121), true)
+        .is_ok());
+    assert!(simulation
+        .place_building(BuildingType::Baricade, (108, 127), true)
+        .is_ok());
+    assert_eq!(
+        simulation.place_building(BuildingType::Baricade, (108, 133), true),
+        Err(PlacementError::SealsBase)
+    );
+    assert_eq!(
+        simulation.last_building_added_id as usize,
+        simulation.building_hash_map.len() - 1
+    );
+}
+
+#[test]
+fn enemies_break_through_barricades_blocking_the_base() {
+    let mut simulation = Simulation::with_seed(2);
+    // box the base in completely so the only way is through a barricade
+    let mut barricade_ids = Vec::new();
+    for (bottom_left, width, height) in [
+        ((108, 115), 34, 2),
+        ((108, 135), 34, 2),
+        ((108, 133), 2, 18),
+        ((140, 133), 2, 18),
+    ] {
+        barricade_ids.push(simulation.add_building(Building {
+            building_type: BuildingType::Baricade,
+            bottom_left,
+            width,
+            height,
+            max_health: 40.,
+            health: 40.,
+        }));
+    }
+    simulation.enemy_alive_list.push(grunt_at((-100., 0.), 80.));
+    let mut stopped_at_barricade = false;
+    // short of the first wave
+    for _ in 0..60 * 12 {
+        simulation.step(TICK);
+        if let Some(enemy) = simulation.enemy_alive_list.first() {
+            stopped_at_barricade |= enemy
+                .building_hit
+                .is_some_and(|id| barricade_ids.contains(&id));
+        }
+    }
+    assert!(stopped_at_barricade);
+    // one wall fell and the enemy went on to attack the base
+    let standing = barricade_ids
+        .iter()
+        .filter(|id| simulation.building_hash_map.contains_key(id))
+        .count();
+    assert_eq!(standing, 3);
+    assert_eq!(simulation.enemy_alive_list[0].building_hit, Some(0));
+    let main_building = &simulation.building_hash_map[&0];
+    assert!(main_building.health < main_building.max_health);
+}
+
+#[test]
+fn destroyed_buildings_turn_to_rubble_and_can_lose_the_game() {
+    let mut simulation = Simulation::with_seed(8);
+    simulation.loss_conditions = LossConditions {
+        all_sentries_lost: true,
+        buildings_lost: None,
+    };
+    let sentry_id = simulation
+        .place_building(BuildingType::Sentry, (40, 44), false)
+        .unwrap();
+    let barricade_id = simulation
+        .place_building(BuildingType::Baricade, (60, 44), false)
+        .unwrap();
+    assert!(!simulation.is_over());
+
+    simulation
+        .building_hash_map
+        .get_mut(&barricade_id)
+        .unwrap()
+        .health = 0.;
+    simulation.destroy_building(barricade_id);
+    assert_eq!(simulation.buildings_lost, 1);
+    assert_eq!(simulation.rubble_list.len(), 1);
+    assert_eq!(simulation.map.map[60][43].terrain, Terrain::Rubble);
+    assert!(simulation.map.map[60][43].building.is_none());
+    assert!(!simulation.is_over());
+
+    // an enemy standing on the sentry's wall wears it down
+    let mut enemy = grunt_at(simulation.building_hash_map[&sentry_id].center(), 500.);
+    enemy.speed = 0;
+    enemy.attack_damage = 60. * 300.;
+    simulation.enemy_alive_list.push(enemy);
+    simulation.step(TICK);
+    simulation.step(TICK);
+    assert!(!simulation.building_hash_map.contains_key(&sentry_id));
+    assert!(simulation.sentry_hash_map.is_empty());
+    assert_eq!(simulation.loss_reason(), Some(LossReason::AllSentriesLost));
+
+    simulation.loss_conditions = LossConditions {
+        all_sentries_lost: false,
+        buildings_lost: Some(2),
+    };
+    assert_eq!(simulation.loss_reason(), Some(LossReason::BuildingsLost(2)));
+}
+
+#[test]
+fn the_main_gun_holds_its_target_until_it_has_ammo() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.main_gun.magazine.clear();
+    simulation.enemy_alive_list.push(grunt_at((0., -100.), 50.));
+    simulation.queue_command(Command::Fire { x: 0., y: 100. });
+    for _ in 0..60 * 3 {
+        simulation.step(TICK);
+    }
+    assert_eq!(simulation.main_gun.fired_count, 0);
+    assert_eq!(simulation.main_gun.target_info_list.len(), 1);
+    assert_eq!(simulation.enemy_alive_list[0].health, 50.);
+
+    // a grunt corpse is worth one round at the default ratio
+    simulation.main_gun.load_corpse(2);
+    assert_eq!(simulation.main_gun.ammo(), 1);
+    simulation.step(TICK);
+    assert_eq!(simulation.main_gun.fired_count, 1);
+    assert_eq!(simulation.main_gun.ammo(), 0);
+    assert!(simulation.main_gun.target_info_list.is_empty());
+}
+
+#[test]
+fn workers_turn_deposited_bodies_into_rounds() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.rooftop_view = false;
+    simulation.main_gun.magazine.clear();
+    simulation.main_gun.rounds_per_corpse_value = 0.25;
+    let body = grunt_at((30., 40.), -1.);
+    let corpse_value = body.corpse_value;
+    simulation.enemy_dead_list.push(body);
+    simulation.worker_list.clear();
+    simulation.worker_list.push(Worker::new(1, (40, 50)));
+    // clicking the same body twice only sends one worker after it
+    simulation.queue_command(Command::CollectBody { x: 30., y: -40. });
+    simulation.queue_command(Command::CollectBody { x: 30., y: -40. });
+    for _ in 0..60 * 3 {
+        simulation.step(TICK);
+    }
+    assert!(simulation.enemy_dead_list.is_empty());
+    assert!(simulation.job_board.is_empty());
+    assert!(simulation.worker_list[0].is_available());
+    let rounds = corpse_value as f32 * 0.25;
+    assert_eq!(simulation.main_gun.ammo(), rounds.floor() as u32);
+    assert_eq!(simulation.main_gun.ammo_progress, rounds.fract());
+}
+
+#[test]
+fn workers_pick_bodies_up_at_the_first_goal_and_deliver_them_at_the_last() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.rooftop_view = false;
+    simulation.main_gun.magazine.clear();
+    simulation.enemy_dead_list.push(grunt_at((-100., 0.), -1.));
+    simulation.worker_list.clear();
+    simulation.worker_list.push(Worker::new(1, (-60, 0)));
+    simulation.queue_command(Command::CollectBody { x: -100., y: 0. });
+    let mut states = vec![WorkerState::Idle];
+    for _ in 0..60 * 10 {
+        simulation.step(TICK);
+        let worker = &simulation.worker_list[0];
+        let state = match worker.state {
+            WorkerState::Working { goal, .. } => WorkerState::Working {
+                goal,
+                time_left: 0.,
+            },
+            state => state,
+        };
+        if states.last() != Some(&state) {
+            states.push(state);
+            // the body leaves the field when it is picked up and is carried until it is delivered
+            let carrying = matches!(
+                state,
+                WorkerState::Travelling { goal: 1 } | WorkerState::Working { goal: 1, .. }
+            );
+            assert_eq!(worker.carrying.is_some(), carrying);
+            assert_eq!(simulation.enemy_dead_list.is_empty(), states.len() > 3);
+        }
+    }
+    assert_eq!(
+        states,
+        [
+            WorkerState::Idle,
+            WorkerState::Travelling { goal: 0 },
+            WorkerState::Working {
+                goal: 0,
+                time_left: 0.
+            },
+            WorkerState::Travelling { goal: 1 },
+            WorkerState::Working {
+                goal: 1,
+                time_left: 0.
+            },
+            WorkerState::Idle,
+        ]
+    );
+    assert!(simulation.enemy_dead_list.is_empty());
+    assert_eq!(simulation.main_gun.ammo(), 1);
+}
+
+#[test]
+fn games_start_with_a_crew_and_hire_more_with_shells() {
+    let mut simulation = Simulation::with_seed(1);
+    assert_eq!(simulation.worker_list.len(), STARTING_WORKERS as usize);
+    assert!(simulation
+        .worker_list
+        .iter()
+        .all(|worker| worker.is_available()));
+
+    let ammo = simulation.main_gun.ammo();
+    simulation.queue_command(Command::HireWorker);
+    simulation.step(TICK);
+    assert_eq!(simulation.worker_list.len(), STARTING_WORKERS as usize + 1);
+    assert_eq!(simulation.main_gun.ammo(), ammo - WORKER_HIRE_COST);
+
+    // a lost worker's id is not handed out again
+    simulation.worker_list[0].health = -1.;
+    simulation.step(TICK);
+    simulation
+        .main_gun
+        .magazine
+        .truncate(WORKER_HIRE_COST as usize - 1);
+    assert_eq!(simulation.hire_worker(), None);
+    simulation.main_gun.load_shell(ShellKind::Standard);
+    assert_eq!(simulation.hire_worker(), Some(STARTING_WORKERS + 2));
+    assert!(simulation.main_gun.magazine.is_empty());
+    let mut worker_ids: Vec<u32> = simulation
+        .worker_list
+        .iter()
+        .map(|worker| worker.id)
+        .collect();
+    worker_ids.sort();
+    worker_ids.dedup();
+    assert_eq!(worker_ids.len(), STARTING_WORKERS as usize + 1);
+}
+
+#[test]
+fn tasks_wait_for_a_worker_when_the_roster_is_empty() {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.rooftop_view = false;
+    simulation.worker_list.clear();
+    simulation.enemy_dead_list.push(grunt_at((-100., 0.), -1.));
+    simulation.queue_command(Command::CollectBody { x: -100., y: 0. });
+    for _ in 0..60 {
+        simulation.step(TICK);
+    }
+    assert_eq!(simulation.job_board.len(), 1);
+    assert_eq!(simulation.enemy_dead_list.len(), 1);
+
+    simulation.queue_command(Command::HireWorker);
+    for _ in 0..60 * 10 {
+        simulation.step(TICK);
+    }
+    assert!(simulation.job_board.is_empty());
+    assert!(simulation.enemy_dead_list.is_empty());
+    assert!(simulation.worker_list[0].is_available());
+}
@@ -0,0 +1,82 @@
+mod common;
+
+use common::grunt_at;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use the_last_hope::enemy::Enemy;
+use the_last_hope::spatial::SpatialIndex;
+
+fn enemies_at(positions: &[(f32, f32)]) -> Vec<Enemy> {
+    positions
+        .iter()
+        .map(|position| grunt_at(*position, 50.))
+        .collect()
+}
+
+fn scattered_enemies(count: usize, seed: u64) -> Vec<Enemy> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let positions: Vec<(f32, f32)> = (0..count)
+        .map(|_| (rng.gen_range(-500.0..500.), rng.gen_range(-500.0..500.)))
+        .collect();
+    enemies_at(&positions)
+}
+
+fn distance_squared(enemy: &Enemy, position: (f32, f32)) -> f32 {
+    (enemy.position.0 - position.0).powi(2) + (enemy.position.1 - position.1).powi(2)
+}
+
+#[test]
+fn queries_find_the_same_enemies_as_walking_the_list() {
+    let enemies = scattered_enemies(2000, 3);
+    let spatial_index = SpatialIndex::new(&enemies);
+    assert_eq!(spatial_index.len(), 2000);
+    let mut rng = StdRng::seed_from_u64(4);
+    for _ in 0..50 {
+        let center = (rng.gen_range(-520.0..520.), rng.gen_range(-520.0..520.));
+        let radius = rng.gen_range(0.0..150.);
+        let in_radius: Vec<usize> = (0..enemies.len())
+            .filter(|index| distance_squared(&enemies[*index], center) <= radius * radius)
+            .collect();
+        assert_eq!(spatial_index.in_radius(center, radius), in_radius);
+
+        let max = (center.0 + radius, center.1 + radius / 2.);
+        let in_rect: Vec<usize> = (0..enemies.len())
+            .filter(|index| {
+                let position = enemies[*index].position;
+                (center.0..=max.0).contains(&position.0) && (center.1..=max.1).contains(&position.1)
+            })
+            .collect();
+        assert_eq!(spatial_index.in_rect(center, max), in_rect);
+
+        let mut by_distance: Vec<usize> = (0..enemies.len()).collect();
+        by_distance.sort_by(|a, b| {
+            distance_squared(&enemies[*a], center)
+                .total_cmp(&distance_squared(&enemies[*b], center))
+                .then(a.cmp(b))
+        });
+        by_distance.truncate(7);
+        assert_eq!(spatial_index.nearest(center, 7), by_distance);
+    }
+}
+
+#[test]
+fn edges_and_ties_are_kept_in_list_order() {
+    let enemies = enemies_at(&[(30., 0.), (-30., 0.), (0., 30.), (600., 600.), (0., -31.)]);
+    let mut spatial_index = SpatialIndex::new(&enemies);
+    // exactly on the radius counts
+    assert_eq!(spatial_index.in_radius((0., 0.), 30.), [0, 1, 2]);
+    assert_eq!(spatial_index.nearest((0., 0.), 4), [0, 1, 2, 4]);
+    // positions off the map still end up in an edge bucket
+    assert_eq!(spatial_index.nearest((500., 500.), 1), [3]);
+    assert_eq!(spatial_index.in_rect((550., 550.), (650., 650.)), [3]);
+    assert_eq!(spatial_index.nearest((0., 0.), 10).len(), 5);
+    assert_eq!(spatial_index.largest_size, 20.);
+
+    spatial_index.rebuild(&enemies[..2]);
+    assert_eq!(spatial_index.nearest((0., 0.), 10), [0, 1]);
+    spatial_index.push(&enemies[4]);
+    assert_eq!(spatial_index.in_radius((0., -31.), 1.), [2]);
+    spatial_index.rebuild(&[]);
+    assert!(spatial_index.is_empty());
+    assert!(spatial_index.nearest((0., 0.), 3).is_empty());
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::grunt_at;
+use the_last_hope::enemy::Enemy;
+use the_last_hope::map::{Building, BuildingType};
+use the_last_hope::worker::{JobBoard, Task, TaskKind, TaskPriority, Worker, WorkerState};
+use the_last_hope::{Command, Simulation};
+
+const TICK: f32 = 1. / 60.;
+
+fn body_at(position: (f32, f32)) -> Enemy {
+    grunt_at(position, -1.)
+}
+
+fn task(priority: TaskPriority) -> Task {
+    Task {
+        id: 0,
+        kind: TaskKind::CollectBody { body_id: 0 },
+        priority,
+        task_times: vec![0.5, 0.5],
+        goals: vec![(0., 0.), (0., 0.)],
+        deposit_at: None,
+    }
+}
+
+/// Ground view simulation with idle workers standing at the given positions
+fn simulation_with_workers(positions: &[(i32, i32)]) -> Simulation {
+    let mut simulation = Simulation::with_seed(1);
+    simulation.rooftop_view = false;
+    simulation.worker_list = positions
+        .iter()
+        .enumerate()
+        .map(|(index, position)| Worker::new(index as u32 + 1, *position))
+        .collect();
+    simulation
+}
+
+#[test]
+fn the_job_board_hands_out_the_most_urgent_then_the_oldest_task() {
+    let mut job_board = JobBoard::default();
+    let far = job_board.post(task(TaskPriority::CollectFar));
+    let near = job_board.post(task(TaskPriority::CollectNearBase));
+    let repair = job_board.post(task(TaskPriority::Repair));
+    let second_near = job_board.post(task(TaskPriority::CollectNearBase));
+    assert!(job_board.cancel(second_near).is_some());
+    assert!(job_board.cancel(second_near).is_none());
+    let third_near = job_board.post(task(TaskPriority::CollectNearBase));
+    let order: Vec<u32> = std::iter::from_fn(|| job_board.take_next())
+        .map(|task| task.id)
+        .collect();
+    assert_eq!(order, [repair, near, third_near, far]);
+}
+
+#[test]
+fn tasks_go_to_the_closest_free_worker() {
+    let mut simulation = simulation_with_workers(&[(-200, 0), (100, 0), (200, 0)]);
+    simulation.enemy_dead_list.push(body_at((150., 0.)));
+    simulation.queue_command(Command::CollectBody { x: 150., y: 0. });
+    simulation.step(TICK);
+    let busy: Vec<u32> = simulation
+        .worker_list
+        .iter()
+        .filter(|worker| !worker.is_available())
+        .map(|worker| worker.id)
+        .collect();
+    // the two nearest are the same distance away, the lower id wins
+    assert_eq!(busy, [2]);
+}
+
+#[test]
+fn repairs_come_before_bodies_and_patch_the_building_up() {
+    let mut simulation = simulation_with_workers(&[(-100, 60)]);
+    let building_id = simulation.add_building(Building {
+        building_type: BuildingType::Baricade,
+        bottom_left: (90, 140),
+        width: 6,
+        height: 2,
+        max_health: 500.,
+        health: 300.,
+    });
+    simulation.enemy_dead_list.push(body_at((-100., 40.)));
+    simulation.queue_command(Command::CollectBody { x: -100., y: -40. });
+    simulation.queue_command(Command::RepairBuilding { building_id });
+    // a building at full health or with a repair on the way is not queued twice
+    simulation.queue_command(Command::RepairBuilding { building_id });
+    simulation.queue_command(Command::RepairBuilding { building_id: 0 });
+    simulation.step(TICK);
+    let task = simulation.worker_list[0].task.as_ref().unwrap();
+    assert_eq!(task.kind, TaskKind::Repair { building_id });
+    assert_eq!(simulation.job_board.len(), 1);
+    for _ in 0..60 * 5 {
+        simulation.step(TICK);
+    }
+    assert_eq!(simulation.building_hash_map[&building_id].health, 400.);
+    // then it goes back for the body
+    assert!(simulation.enemy_dead_list.is_empty());
+}
+
+#[test]
+fn cancelled_tasks_are_dropped_where_they_stand() {
+    let mut simulation = simulation_with_workers(&[(-60, 0)]);
+    simulation.enemy_dead_list.push(body_at((-100., 0.)));
+    simulation.enemy_dead_list.push(body_at((-100., 60.)));
+    simulation.queue_command(Command::CollectBody { x: -100., y: 0. });
+    simulation.queue_command(Command::CollectBody { x: -100., y: -60. });
+    simulation.step(TICK);
+    let queued = simulation.task_at((-100., 60.)).unwrap();
+    let underway = simulation.task_at((-100., 0.)).unwrap();
+    simulation.queue_command(Command::CancelTask { task_id: queued });
+    simulation.step(TICK);
+    assert!(simulation.job_board.is_empty());
+
+    // a worker carrying the body leaves it on the ground when its task is cancelled
+    while simulation.worker_list[0].carrying.is_none() {
+        simulation.step(TICK);
+    }
+    simulation.queue_command(Command::CancelTask { task_id: underway });
+    simulation.step(TICK);
+    let worker = &simulation.worker_list[0];
+    assert_eq!(worker.state, WorkerState::Idle);
+    assert_eq!(simulation.enemy_dead_list.len(), 2);
+    let dropped_at = (worker.position.0 as f32, worker.position.1 as f32);
+    assert!(simulation
+        .enemy_dead_list
+        .iter()
+        .any(|body| body.position == dropped_at));
+    assert_eq!(simulation.task_at((-100., 60.)), None);
+
+    // both bodies are free to be collected again
+    simulation.queue_command(Command::CollectBody { x: -100., y: -60. });
+    simulation.queue_command(Command::CollectBody {
+        x: dropped_at.0,
+        y: -dropped_at.1,
+    });
+    simulation.step(TICK);
+    assert!(simulation.task_at((-100., 60.)).is_some());
+    assert!(simulation.task_at(dropped_at).is_some());
+}
+
+#[test]
+fn auto_collect_queues_every_body_in_range_once() {
+    let mut simulation = simulation_with_workers(&[]);
+    simulation.auto_collect_radius = 200.;
+    simulation.enemy_dead_list.push(body_at((-100., 0.)));
+    simulation.enemy_dead_list.push(body_at((0., 180.)));
+    simulation.enemy_dead_list.push(body_at((300., 0.)));
+    simulation.step(TICK);
+    assert!(simulation.job_board.is_empty());
+    simulation.queue_command(Command::ToggleAutoCollect);
+    for _ in 0..10 {
+        simulation.step(TICK);
+    }
+    let mut priorities: Vec<TaskPriority> = simulation
+        .job_board
+        .tasks
+        .iter()
+        .map(|task| task.priority)
+        .collect();
+    priorities.sort();
+    assert_eq!(
+        priorities,
+        [TaskPriority::CollectNearBase, TaskPriority::CollectFar]
+    );
+}
+
+#[test]
+fn bodies_lying_on_the_same_spot_are_collected_one_by_one() {
+    let mut simulation = simulation_with_workers(&[(0, 0)]);
+    simulation.auto_collect_radius = 200.;
+    simulation.enemy_dead_list.push(body_at((-100., 0.)));
+    simulation.enemy_dead_list.push(body_at((-100., 0.)));
+    simulation.queue_command(Command::ToggleAutoCollect);
+    simulation.step(TICK);
+    // each body gets its own task even though they lie on the same point
+    assert_eq!(simulation.job_board.tasks.len(), 1);
+    assert!(simulation.worker_list[0].task.is_some());
+    for _ in 0..60 * 30 {
+        simulation.step(TICK);
+    }
+    assert!(simulation.enemy_dead_list.is_empty());
+    assert!(simulation.job_board.is_empty());
+}